@@ -1,17 +1,27 @@
-use std::iter::Peekable;
+use std::{fmt, iter::Peekable};
 use owned_chars::OwnedChars;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     // Operations
-    Get, Put, Update, Create, Delete,
+    Get, Put, Update, Create, Delete, Alter, Truncate,
+    Describe, Show,
+    Begin, Commit, Rollback,
+    Explain,
     // Keywords
-    In, From, Where,
-    Table, Database,
+    In, From, Where, Limit, Offset,
+    Join, On, Distinct, Exists, As, AddKeyword, Drop, Rename, Column, To, Csv, Conflict, Set,
+    Order, By, Asc, Desc, Group, Having,
+    Table, Tables, Database,
     // Type Keywords
-    NumberType, TextType,
+    NumberType, TextType, BooleanType,
+    // Column Modifiers
+    Primary, Unique, NotNull, Default, Auto,
+    // Aggregate Functions
+    Count, Sum, Avg, Min, Max,
     // Logical Operators
-    Equal, NotEqual,
+    Equal, NotEqual, EqualIgnoringCase,
+    Like, Between, Is,
     LessThan, LessThanOrEqual,
     GreaterThan, GreaterThanOrEqual,
     And, Or, Xor, Not,
@@ -19,93 +29,291 @@ pub enum Token {
     Add, Subtract, Divide,
     Power, Modulus,
     // Misc
-    Star, Comma, Period, Colon,
+    Star, Comma, Period, Colon, Semicolon,
+    // `?`, which stands for a value bound later.
+    Placeholder,
     LeftParenthesis, RightParenthesis,
     LeftBracket, RightBracket,
     // Literals
     Integer(i64), Float(f64), String(String),
-    None, Identifier(String)
+    Boolean(bool), None, Identifier(String)
+}
+
+#[derive(Debug)]
+pub struct LexError {
+    // The byte offset into the source
+    // where the error was found.
+    pub offset: usize,
+    pub message: String
+}
+
+impl Token {
+    // How the token is referred to in error messages.
+    pub fn describe(&self) -> String {
+        let keyword = match self {
+            Token::Integer(number) => { return format!("number `{}`", number); },
+            Token::Float(number) => { return format!("number `{}`", number); },
+            Token::String(string) => { return format!("string \"{}\"", string); },
+            Token::Boolean(value) => { return format!("`{}`", value); },
+            Token::Identifier(name) => { return format!("identifier `{}`", name); },
+            Token::Get => "get",
+            Token::Put => "put",
+            Token::Update => "update",
+            Token::Create => "create",
+            Token::Delete => "delete",
+            Token::Alter => "alter",
+            Token::Truncate => "truncate",
+            Token::Describe => "describe",
+            Token::Show => "show",
+            Token::Begin => "begin",
+            Token::Commit => "commit",
+            Token::Rollback => "rollback",
+            Token::Explain => "explain",
+            Token::In => "in",
+            Token::From => "from",
+            Token::Where => "where",
+            Token::Limit => "limit",
+            Token::Offset => "offset",
+            Token::Join => "join",
+            Token::On => "on",
+            Token::Distinct => "distinct",
+            Token::Exists => "exists",
+            Token::As => "as",
+            Token::Drop => "drop",
+            Token::AddKeyword => "add",
+            Token::Rename => "rename",
+            Token::Column => "column",
+            Token::To => "to",
+            Token::Csv => "csv",
+            Token::Conflict => "conflict",
+            Token::Set => "set",
+            Token::Order => "order",
+            Token::By => "by",
+            Token::Group => "group",
+            Token::Having => "having",
+            Token::Asc => "asc",
+            Token::Desc => "desc",
+            Token::Table => "table",
+            Token::Tables => "tables",
+            Token::Database => "database",
+            Token::NumberType => "number",
+            Token::TextType => "text",
+            Token::BooleanType => "bool",
+            Token::Primary => "primary",
+            Token::Unique => "unique",
+            Token::NotNull => "notnull",
+            Token::Default => "default",
+            Token::Auto => "auto",
+            Token::Count => "count",
+            Token::Sum => "sum",
+            Token::Avg => "avg",
+            Token::Min => "min",
+            Token::Max => "max",
+            Token::Equal => "=",
+            Token::NotEqual => "!=",
+            Token::EqualIgnoringCase => "ieq",
+            Token::Like => "like",
+            Token::Between => "between",
+            Token::Is => "is",
+            Token::LessThan => "<",
+            Token::LessThanOrEqual => "<=",
+            Token::GreaterThan => ">",
+            Token::GreaterThanOrEqual => ">=",
+            Token::And => "and",
+            Token::Or => "or",
+            Token::Xor => "xor",
+            Token::Not => "!",
+            Token::Add => "+",
+            Token::Subtract => "-",
+            Token::Divide => "/",
+            Token::Power => "**",
+            Token::Modulus => "%",
+            Token::Star => "*",
+            Token::Comma => ",",
+            Token::Period => ".",
+            Token::Colon => ":",
+            Token::Semicolon => ";",
+            Token::Placeholder => "?",
+            Token::LeftParenthesis => "(",
+            Token::RightParenthesis => ")",
+            Token::LeftBracket => "[",
+            Token::RightBracket => "]",
+            Token::None => "none"
+        };
+        format!("`{}`", keyword)
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.describe())
+    }
 }
 
 pub struct Lexer {
     src: Peekable<OwnedChars>,
-    cur: Option<char>
+    cur: Option<char>,
+    // The byte offset of `cur`.
+    offset: usize
 }
 
 impl Lexer {
     pub fn new() -> Self {
         // Placeholder values.
-        Lexer{src: OwnedChars::from_string(String::new()).peekable(), cur: None}
+        Lexer{src: OwnedChars::from_string(String::new()).peekable(), cur: None, offset: 0}
     }
 
     fn next(&mut self) -> Option<char> {
+        if let Some(c) = self.cur {
+            self.offset += c.len_utf8();
+        }
         self.cur = self.src.next();
         self.cur
     }
 
+    fn error(&self, offset: usize, message: &str) -> LexError {
+        LexError{offset: offset, message: String::from(message)}
+    }
+
     fn peek(&mut self) -> Option<&char> {
         self.src.peek()
     }
 
+    // Only advances if the next char is the expected one.
     fn consume(&mut self, expected: char) -> bool {
-        let c = self.next().unwrap();
-        c == expected
+        if self.peek() == Some(&expected) {
+            let _ = self.next();
+            return true;
+        }
+        false
     }
 
-    // `stop_condition` closures can safely call `unwrap`,
-    // on the current char, because this function ensures
-    // that it isn't None before calling `stop_condition`.
+    // Stops at `stop_condition` or at the end of the
+    // source, whichever comes first, so a token cut
+    // short by the end of the query just ends there.
     fn push_until<F>(&mut self, buffer: &mut String, stop_condition: F)
-      where F: Fn(Option<&char>) -> bool {
-        while self.peek() != None && !stop_condition(self.peek()) {
-            buffer.push(self.next().unwrap());
+      where F: Fn(&char) -> bool {
+        while self.peek().is_some_and(|c| !stop_condition(c)) {
+            let Some(c) = self.next() else { break; };
+            buffer.push(c);
         }
     }
 
-    fn parse_string(&mut self) -> Option<Token> {
-        let mut string = String::from(self.cur.unwrap());
-        self.push_until(&mut string, |c: Option<&char>| *c.unwrap() == '"');
-        if !self.consume('"') {
-            return None;
+    // Expects the opening quote to be the current
+    // char, and consumes everything up to and
+    // including the closing one. Escape sequences
+    // other than \", \\, \n and \t are kept as
+    // they are, backslash included.
+    fn parse_string(&mut self) -> Result<Token, LexError> {
+        let start = self.offset;
+        let mut string = String::new();
+        loop {
+            let Some(c) = self.next() else {
+                return Err(self.error(start, "unterminated string"));
+            };
+            match c {
+                '"' => break,
+                '\\' => {
+                    let Some(escaped) = self.next() else {
+                        return Err(self.error(start, "unterminated string"));
+                    };
+                    match escaped {
+                        '"' => string.push('"'),
+                        '\\' => string.push('\\'),
+                        'n' => string.push('\n'),
+                        't' => string.push('\t'),
+                        _ => {
+                            string.push('\\');
+                            string.push(escaped);
+                        }
+                    }
+                },
+                _ => string.push(c)
+            }
         }
-        Some(Token::String(string))
-    }
-
-    fn parse_number(&mut self) -> Token {
-        let is_valid_number_char = |c: char| {
-            // Support negative, floating
-            // point and hexadecimal numbers.
-            c.is_numeric()
-            || c == '-'
-            || c == '.'
-            || c == 'x'
-            || c == 'a' || c == 'b' || c == 'c'
-            || c == 'd' || c == 'e' || c == 'f'
-        };
+        Ok(Token::String(string))
+    }
 
-        let mut number = String::from(self.cur.unwrap());
-        let stop_condition = |c: Option<&char>| {
-            // The Rust Programming Language.
-            let value = (*c.unwrap()).to_lowercase().next().unwrap();
-            !is_valid_number_char(value)
-        };
-        self.push_until(&mut number, stop_condition);
+    // Expects the opening backtick to be the current char.
+    // Whatever's between the backticks is an identifier,
+    // even if it's a keyword, so a column can be called
+    // where, as long as it's always written in backticks.
+    fn parse_quoted_identifier(&mut self) -> Result<Token, LexError> {
+        let start = self.offset;
+        let mut identifier = String::new();
+        loop {
+            match self.next() {
+                Some('`') => break,
+                Some(c) => identifier.push(c),
+                None => { return Err(self.error(start, "unterminated identifier")); }
+            }
+        }
+        if identifier.is_empty() {
+            return Err(self.error(start, "empty identifier"));
+        }
+        Ok(Token::Identifier(identifier))
+    }
 
-        number = number.to_lowercase();
-        if number.contains('.') {
-            return Token::Float(number.parse::<f64>().unwrap())
+    // `first` is the number's first digit, which has
+    // already been read. Numbers are either hexadecimal
+    // (`0xFF`), or decimal with an optional fractional
+    // part (`3.14`). Scanning stops at the first char
+    // that can't be part of the number, so `1.2.3` is
+    // `1.2`, `.` and `3`. Negative numbers are `-`
+    // followed by a number, which the parser deals with.
+    fn parse_number(&mut self, first: char) -> Result<Token, LexError> {
+        let start = self.offset;
+        let mut number = String::from(first);
+
+        if number == "0" && (self.consume('x') || self.consume('X')) {
+            let mut digits = String::new();
+            self.push_until(&mut digits, |c: &char| !c.is_ascii_hexdigit());
+            if digits.is_empty() {
+                return Err(self.error(start, "invalid number `0x`"));
+            }
+            return i64::from_str_radix(digits.as_str(), 16).map(Token::Integer).map_err(|_| {
+                self.too_big(start, format!("0x{}", digits).as_str())
+            });
         }
-        else if number.contains('x') {
-            return Token::Integer(
-                i64::from_str_radix(number.trim_start_matches("0x"), 16).unwrap());
+
+        self.push_until(&mut number, |c: &char| !c.is_ascii_digit());
+        if self.consume('.') {
+            number.push('.');
+            self.push_until(&mut number, |c: &char| !c.is_ascii_digit());
+            return number.parse::<f64>().map(Token::Float).map_err(|_| {
+                self.error(start, format!("invalid number `{}`", number).as_str())
+            });
         }
 
-        Token::Integer(number.parse::<i64>().unwrap())
+        // Only digits get this far, so the
+        // number can only be too big.
+        number.parse::<i64>().map(Token::Integer).map_err(|_| self.too_big(start, number.as_str()))
+    }
+
+    // Integers are 64-bit, and rather than quietly turning
+    // bigger ones into floats (and losing precision), they
+    // aren't allowed.
+    fn too_big(&self, start: usize, number: &str) -> LexError {
+        self.error(start, format!("`{}` is too big for an integer, which can be at most {}", number, i64::MAX).as_str())
     }
 
-    fn parse_keyword_or_identifier(&mut self) -> Token {
-        let mut string = String::from(self.cur.unwrap());
-        self.push_until(&mut string, |c: Option<&char>| !c.unwrap().is_alphanumeric());
+    fn parse_keyword_or_identifier(&mut self, first: char) -> Token {
+        let mut string = String::from(first);
+        self.push_until(&mut string, |c: &char| !c.is_alphanumeric() && *c != '_');
+
+        // Aggregate function names are only keywords when
+        // they're being called, so that they can still be
+        // used as column names.
+        if self.peek() == Some(&'(') {
+            match string.to_lowercase().as_str() {
+                "count" => { return Token::Count; },
+                "sum" => { return Token::Sum; },
+                "avg" => { return Token::Avg; },
+                "min" => { return Token::Min; },
+                "max" => { return Token::Max; },
+                _ => {}
+            }
+        }
 
         match string.to_lowercase().as_str() {
             "get" => Token::Get,
@@ -113,16 +321,61 @@ impl Lexer {
             "update" => Token::Update,
             "create" => Token::Create,
             "delete" => Token::Delete,
+            "alter" => Token::Alter,
+            "truncate" => Token::Truncate,
+            "describe" => Token::Describe,
+            "show" => Token::Show,
+            "begin" => Token::Begin,
+            "commit" => Token::Commit,
+            "rollback" => Token::Rollback,
+            "explain" => Token::Explain,
+            // Not to be confused with `+`, which
+            // is `Token::Add`.
+            "add" => Token::AddKeyword,
             "in" => Token::In,
             "from" => Token::From,
             "where" => Token::Where,
+            "limit" => Token::Limit,
+            "offset" => Token::Offset,
+            "join" => Token::Join,
+            "on" => Token::On,
+            "distinct" => Token::Distinct,
+            "exists" => Token::Exists,
+            "as" => Token::As,
+            "drop" => Token::Drop,
+            "rename" => Token::Rename,
+            "column" => Token::Column,
+            "to" => Token::To,
+            "csv" => Token::Csv,
+            "conflict" => Token::Conflict,
+            "set" => Token::Set,
+            "order" => Token::Order,
+            "by" => Token::By,
+            "group" => Token::Group,
+            "having" => Token::Having,
+            "asc" => Token::Asc,
+            "desc" => Token::Desc,
             "table" => Token::Table,
+            "tables" => Token::Tables,
             "database" => Token::Database,
             "and" => Token::And,
             "or" => Token::Or,
             "xor" => Token::Xor,
+            "ieq" => Token::EqualIgnoringCase,
+            "like" => Token::Like,
+            "between" => Token::Between,
+            "is" => Token::Is,
+            "not" => Token::Not,
             "number" => Token::NumberType,
             "text" => Token::TextType,
+            "bool" => Token::BooleanType,
+            "primary" => Token::Primary,
+            "unique" => Token::Unique,
+            "notnull" => Token::NotNull,
+            "default" => Token::Default,
+            "auto" => Token::Auto,
+            "true" => Token::Boolean(true),
+            "false" => Token::Boolean(false),
             "none" => Token::None,
             _ => Token::Identifier(string)
         }
@@ -132,17 +385,30 @@ impl Lexer {
     // mutable references to `lexer`, so it's a
     // static method that accepts a mutable Lexer
     // reference.
-    pub fn lex(lexer: &mut Lexer, src: String) -> Vec<Token> {
+    pub fn lex(lexer: &mut Lexer, src: String) -> Result<Vec<Token>, LexError> {
         lexer.src = OwnedChars::from_string(src).peekable();
         lexer.cur = None;
+        lexer.offset = 0;
 
         let mut tokens: Vec<Token> = Vec::new();
 
         while let Some(c) = lexer.next() {
             match c {
-                ' ' | '\r' | '\n'  => continue,
+                ' ' | '\t' | '\r' | '\n'  => continue,
                 '+' => tokens.push(Token::Add),
-                '-' => tokens.push(Token::Subtract),
+                '-' => {
+                    // `--` starts a comment that runs to the
+                    // end of the line. (So `5--3` needs a
+                    // space to mean `5 - -3`.)
+                    if lexer.consume('-') {
+                        while lexer.peek().is_some_and(|c| *c != '\n') {
+                            let _ = lexer.next();
+                        }
+                    }
+                    else {
+                        tokens.push(Token::Subtract);
+                    }
+                },
                 '*' => {
                     if lexer.consume('*') {
                         tokens.push(Token::Power)
@@ -152,6 +418,7 @@ impl Lexer {
                     }
                 },
                 '/' => tokens.push(Token::Divide),
+                '^' => tokens.push(Token::Power),
                 '%' => tokens.push(Token::Modulus),
                 ',' => tokens.push(Token::Comma),
                 '.' => tokens.push(Token::Period),
@@ -160,11 +427,10 @@ impl Lexer {
                 '[' => tokens.push(Token::LeftBracket),
                 ']' => tokens.push(Token::RightBracket),
                 ':' => tokens.push(Token::Colon),
-                '"' => {
-                    let _ = lexer.next();
-                    let string = lexer.parse_string().unwrap(); 
-                    tokens.push(string);
-                },
+                ';' => tokens.push(Token::Semicolon),
+                '?' => tokens.push(Token::Placeholder),
+                '"' => tokens.push(lexer.parse_string()?),
+                '`' => tokens.push(lexer.parse_quoted_identifier()?),
                 '<' => {
                     if lexer.consume('=') {
                         tokens.push(Token::LessThanOrEqual);
@@ -190,10 +456,159 @@ impl Lexer {
                         tokens.push(Token::Not);
                     }
                 }
-                '0'..='9' => tokens.push(lexer.parse_number()),
-                _ => tokens.push(lexer.parse_keyword_or_identifier()),
+                '0'..='9' => tokens.push(lexer.parse_number(c)?),
+                _ if c.is_alphabetic() || c == '_' => tokens.push(lexer.parse_keyword_or_identifier(c)),
+                _ => {
+                    let message = format!("unexpected character `{}`", c);
+                    return Err(lexer.error(lexer.offset, message.as_str()));
+                }
             }
         }
-        tokens
+        Ok(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lex(src: &str) -> Result<Vec<Token>, LexError> {
+        Lexer::lex(&mut Lexer::new(), String::from(src))
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn add_is_a_keyword_rather_than_plus() {
+        assert_eq!(lex("add + ADD").unwrap(), [Token::AddKeyword, Token::Add, Token::AddKeyword]);
+    }
+
+    #[test]
+    fn strings_keep_every_character() {
+        assert_eq!(lex("\"\"").unwrap(), [Token::String(String::new())]);
+        assert_eq!(lex("\"j\"").unwrap(), [Token::String(String::from("j"))]);
+        assert_eq!(lex("\"jim smith\"").unwrap(), [Token::String(String::from("jim smith"))]);
+        assert_eq!(lex("\"123\" 123").unwrap(), [Token::String(String::from("123")), Token::Integer(123)]);
+        assert_eq!(lex("\"a1\",\"b2\"").unwrap(),
+                   [Token::String(String::from("a1")), Token::Comma, Token::String(String::from("b2"))]);
+    }
+
+    #[test]
+    fn a_lone_exclamation_mark_is_not() {
+        assert_eq!(lex("!").unwrap(), [Token::Not]);
+        assert_eq!(lex("!=").unwrap(), [Token::NotEqual]);
+        assert_eq!(lex("! =").unwrap(), [Token::Not, Token::Equal]);
+        assert_eq!(lex("a != !b").unwrap(), [Token::Identifier(String::from("a")), Token::NotEqual,
+                                             Token::Not, Token::Identifier(String::from("b"))]);
+        assert_eq!(lex("x = 1 !").unwrap().last(), Some(&Token::Not));
+    }
+
+    #[test]
+    fn strings_can_have_escapes_in() {
+        assert_eq!(lex(r#""say \"hi\"""#).unwrap(), [Token::String(String::from("say \"hi\""))]);
+        assert_eq!(lex(r#""a\\b""#).unwrap(), [Token::String(String::from("a\\b"))]);
+        assert_eq!(lex(r#""a\nb""#).unwrap(), [Token::String(String::from("a\nb"))]);
+        assert_eq!(lex(r#""a\tb""#).unwrap(), [Token::String(String::from("a\tb"))]);
+        // Anything else is kept as it is.
+        assert_eq!(lex(r#""50\%""#).unwrap(), [Token::String(String::from("50\\%"))]);
+    }
+
+    #[test]
+    fn unterminated_strings_are_an_error() {
+        for src in ["\"abc", "x = \"abc\\\"", "\"abc\\"] {
+            assert_eq!(lex(src).unwrap_err().message, "unterminated string");
+        }
+    }
+
+    #[test]
+    fn errors_say_where_they_are() {
+        let error = lex("get # from t").unwrap_err();
+        assert_eq!((error.offset, error.message.as_str()), (4, "unexpected character `#`"));
+        // Offsets are in bytes, not chars.
+        assert_eq!(lex("\"é\" #").unwrap_err().offset, 5);
+        assert_eq!(lex("put [\"abc] in t").unwrap_err().offset, 5);
+        assert_eq!(lex("x = `abc").unwrap_err().offset, 4);
+        let error = lex("x = 99999999999999999999").unwrap_err();
+        assert_eq!(error.offset, 4);
+        assert!(error.message.starts_with("`99999999999999999999` is too big"));
+    }
+
+    #[test]
+    fn parentheses_are_tokens() {
+        assert_eq!(lex("( )").unwrap(), [Token::LeftParenthesis, Token::RightParenthesis]);
+        assert_eq!(lex("(a)").unwrap(), [Token::LeftParenthesis, Token::Identifier(String::from("a")),
+                                         Token::RightParenthesis]);
+    }
+
+    #[test]
+    fn comments_run_to_the_end_of_the_line() {
+        let get = [Token::Get, Token::Star, Token::From, Token::Identifier(String::from("t"))];
+        assert_eq!(lex("get * from t -- everything").unwrap(), get);
+        assert_eq!(lex("-- everything\nget * from t").unwrap(), get);
+        assert_eq!(lex("get * -- everything\nfrom t --").unwrap(), get);
+        assert_eq!(lex("\"a -- b\"").unwrap(), [Token::String(String::from("a -- b"))]);
+        assert_eq!(lex("5 - -3").unwrap(), [Token::Integer(5), Token::Subtract, Token::Subtract, Token::Integer(3)]);
+        assert_eq!(lex("5--3").unwrap(), [Token::Integer(5)]);
+    }
+
+    #[test]
+    fn minus_signs_are_always_their_own_token() {
+        assert_eq!(lex("-5").unwrap(), [Token::Subtract, Token::Integer(5)]);
+        assert_eq!(lex("5 - 3").unwrap(), [Token::Integer(5), Token::Subtract, Token::Integer(3)]);
+        assert_eq!(lex("5-3").unwrap(), [Token::Integer(5), Token::Subtract, Token::Integer(3)]);
+        assert_eq!(lex("a - b").unwrap(), [Token::Identifier(String::from("a")), Token::Subtract,
+                                           Token::Identifier(String::from("b"))]);
+    }
+
+    #[test]
+    fn numbers_are_decimal_or_hexadecimal() {
+        assert_eq!(lex("123").unwrap(), [Token::Integer(123)]);
+        assert_eq!(lex("2.75").unwrap(), [Token::Float(2.75)]);
+        assert_eq!(lex("0xFF 0Xff").unwrap(), [Token::Integer(255), Token::Integer(255)]);
+        assert_eq!(lex("0.5 007").unwrap(), [Token::Float(0.5), Token::Integer(7)]);
+        assert_eq!(lex("1.2.3").unwrap(), [Token::Float(1.2), Token::Period, Token::Integer(3)]);
+    }
+
+    #[test]
+    fn malformed_numbers_are_an_error() {
+        assert_eq!(lex("x = 0x").unwrap_err().message, "invalid number `0x`");
+        assert_eq!(lex("0xG").unwrap_err().message, "invalid number `0x`");
+        assert!(lex("0x10000000000000000").unwrap_err().message.starts_with("`0x10000000000000000` is too big"));
+    }
+
+    #[test]
+    fn tokens_display_the_way_errors_describe_them() {
+        assert_eq!(Token::Where.to_string(), "`where`");
+        assert_eq!(Token::AddKeyword.to_string(), "`add`");
+        assert_eq!(Token::Integer(5).to_string(), "number `5`");
+        assert_eq!(Token::String(String::from("jim")).to_string(), "string \"jim\"");
+        assert_eq!(Token::Identifier(String::from("ID")).to_string(), "identifier `ID`");
+    }
+
+    #[test]
+    fn backticks_make_keywords_identifiers() {
+        for src in ["where", "WHERE", "Where", "wHeRe"] {
+            assert_eq!(lex(src).unwrap(), [Token::Where]);
+        }
+        assert_eq!(lex("`where`").unwrap(), [Token::Identifier(String::from("where"))]);
+        assert_eq!(lex("`WHERE`").unwrap(), [Token::Identifier(String::from("WHERE"))]);
+        assert_eq!(lex("`where` where").unwrap(), [Token::Identifier(String::from("where")), Token::Where]);
+        assert_eq!(lex("`first name`").unwrap(), [Token::Identifier(String::from("first name"))]);
+        // Identifiers keep their case.
+        assert_eq!(lex("Name").unwrap(), [Token::Identifier(String::from("Name"))]);
+    }
+
+    #[test]
+    fn input_can_end_in_the_middle_of_a_token() {
+        assert_eq!(lex("x = 1").unwrap().last(), Some(&Token::Integer(1)));
+        assert_eq!(lex("x = 1.").unwrap()[2..], [Token::Float(1.0)]);
+        assert_eq!(lex("x = 1.5").unwrap().last(), Some(&Token::Float(1.5)));
+        assert_eq!(lex("x = 0xf").unwrap().last(), Some(&Token::Integer(15)));
+        assert_eq!(lex("get").unwrap(), [Token::Get]);
+        assert_eq!(lex("x").unwrap(), [Token::Identifier(String::from("x"))]);
+        assert_eq!(lex("x !").unwrap().last(), Some(&Token::Not));
+        assert_eq!(lex("x <").unwrap().last(), Some(&Token::LessThan));
+        // Strings and quoted names have to be closed.
+        for src in ["\"", "x = \"a", "x = \"a\\", "`", "`a"] {
+            assert!(lex(src).is_err(), "{}", src);
+        }
+    }
+}
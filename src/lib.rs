@@ -3,22 +3,27 @@
 mod parser;
 mod lexer;
 
-use std::{any::{Any, TypeId}, collections::HashMap, fs::File, path::{Path, PathBuf}, io::{self, Write}};
-use serde::{Deserialize, Serialize};
+// So queries can be built and run from
+// Rust, without writing them out first.
+pub use parser::{Query, Operation, Expression, ExpressionType, Aggregate, AggregateFunction, Join, Alteration};
+
+use std::{any::{Any, TypeId}, cmp::Ordering, collections::{HashMap, HashSet}, fmt, fs::{self, File}, path::{Path, PathBuf}, io::{self, Write}};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize, ser::SerializeMap};
 use crate::parser::*;
 use crate::lexer::*;
 
 pub fn run() -> io::Result<()> {
     // Test code
-    let mut database = Database::new(String::from("business"), DatabaseConfig::default());
+    let mut database = Database::new(String::from("business"), DatabaseConfig::default()).unwrap();
     let customers = database.new_table(
         String::from("customers"),
         vec![Column::new(String::from("Name"), FieldType::Text),
             Column::new(String::from("ID"), FieldType::Number)]
         ).unwrap();
-    customers.new_row(vec![FieldValue::Text(String::from("james")), FieldValue::Integer(1)]);
-    customers.new_row(vec![FieldValue::Text(String::from("jim")), FieldValue::Integer(2)]);
-    customers.new_row(vec![FieldValue::Text(String::from("jimmy")), FieldValue::Integer(3)]);
+    customers.new_row(vec![FieldValue::Text(String::from("james")), FieldValue::Integer(1)]).unwrap();
+    customers.new_row(vec![FieldValue::Text(String::from("jim")), FieldValue::Integer(2)]).unwrap();
+    customers.new_row(vec![FieldValue::Text(String::from("jimmy")), FieldValue::Integer(3)]).unwrap();
     // database.save();
     // let mut database = Database::from_file(Path::new("./business")).unwrap();
     // let mut database = Database::new(String::from("default"), DatabaseConfig::default());
@@ -34,55 +39,332 @@ pub fn run() -> io::Result<()> {
         if input.starts_with("q") {
             break;
         }
+        // Commands start with a `.`, so they can't
+        // be mistaken for queries.
+        if input.trim_start().starts_with('.') {
+            match run_command(&mut database, &input) {
+                Ok(message) => println!("{}", message),
+                Err(error) => println!("Error: {}", error)
+            }
+            continue;
+        }
         // Lexing, parsing, and interpreting
-        let tokens = Lexer::lex(&mut lexer, input);
-        let query = Parser::parse(&mut parser, tokens);
-        // println!("{:#?}", query);
-        let result = database.run_query(query);
-        // println!("{:#?}", result);
-        result.unwrap().print();
+        let tokens = match Lexer::lex(&mut lexer, input) {
+            Ok(tokens) => tokens,
+            Err(error) => {
+                println!("Error at {}: {}", error.offset, error.message);
+                continue;
+            }
+        };
+        // Nothing runs unless every query parses, and
+        // the first one that fails stops the rest.
+        let queries = match Parser::parse_all(&mut parser, tokens) {
+            Ok(queries) => queries,
+            Err(error) => {
+                println!("Error at token {}: {}", error.position, error.message());
+                continue;
+            }
+        };
+        for query in queries {
+            // println!("{:#?}", query);
+            match database.run_query(query) {
+                // println!("{:#?}", result);
+                Ok(result) => result.print(),
+                Err(error) => {
+                    println!("Error: {}", error);
+                    break;
+                }
+            }
+        }
     }
 
     Ok(())
 }
 
+// Runs a REPL command, like `.save`, against `database`,
+// returning a message describing what happened. `.load`
+// and `.open` replace `database` with the one at the
+// given path, which is then saved back to that path.
+pub fn run_command(database: &mut Database, command: &str) -> Result<String, CoilError> {
+    let mut words = command.split_whitespace();
+    let name = words.next().unwrap_or("");
+    let path = words.next().map(Path::new);
+    if words.next().is_some() {
+        return Err(CoilError::InvalidCommand(format!("too many arguments to `{}`", name)));
+    }
+    match (name, path) {
+        (".save", None) => {
+            database.save()?;
+            Ok(format!("Saved `{}` to {}", database.name, database.file_path().display()))
+        },
+        (".load", Some(path)) => {
+            let mut loaded = Database::from_file(path)?;
+            loaded.move_to(path)?;
+            *database = loaded;
+            Ok(format!("Loaded `{}` from {}", database.name, path.display()))
+        },
+        // Unlike `.load`, this starts a new database
+        // if there isn't one at the path yet.
+        (".open", Some(path)) => {
+            if path.exists() {
+                let mut opened = Database::from_file(path)?;
+                opened.move_to(path)?;
+                *database = opened;
+                Ok(format!("Opened `{}` from {}", database.name, path.display()))
+            }
+            else {
+                let (name, directory) = split_path(path);
+                *database = Database::new(name, DatabaseConfig::new(directory))?;
+                Ok(format!("Created `{}` at {}", database.name, path.display()))
+            }
+        },
+        (".schema", None) => Ok(database.to_ddl().trim_end().to_string()),
+        (".save" | ".schema", Some(_)) => Err(CoilError::InvalidCommand(format!("`{}` doesn't take any arguments", name))),
+        (".load" | ".open", None) => Err(CoilError::InvalidCommand(format!("`{}` needs a path", name))),
+        _ => Err(CoilError::InvalidCommand(format!("unknown command `{}`", name)))
+    }
+}
+
+// Holds several databases at once, and sends each
+// query to the one its table is qualified with (like
+// `analytics.events`), or to the current database if
+// it isn't qualified.
+#[derive(Debug, Default)]
+pub struct Workspace {
+    databases: HashMap<String, Database>,
+    current: Option<String>
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Workspace{databases: HashMap::new(), current: None}
+    }
+
+    // The first database attached becomes
+    // the current one.
+    pub fn attach(&mut self, database: Database) -> Result<(), CoilError> {
+        if self.databases.contains_key(&database.name) {
+            return Err(CoilError::DatabaseAlreadyExists);
+        }
+        if self.current.is_none() {
+            self.current = Some(database.name.clone());
+        }
+        self.databases.insert(database.name.clone(), database);
+        Ok(())
+    }
+
+    pub fn detach(&mut self, name: &str) -> Result<Database, CoilError> {
+        let database = self.databases.remove(name).ok_or(CoilError::DatabaseDoesntExist)?;
+        if self.current.as_deref() == Some(name) {
+            self.current = None;
+        }
+        Ok(database)
+    }
+
+    pub fn set_current(&mut self, name: &str) -> Result<(), CoilError> {
+        if !self.databases.contains_key(name) {
+            return Err(CoilError::DatabaseDoesntExist);
+        }
+        self.current = Some(String::from(name));
+        Ok(())
+    }
+
+    pub fn current(&self) -> Option<&Database> {
+        self.databases.get(self.current.as_deref()?)
+    }
+
+    pub fn get_database(&self, name: &str) -> Option<&Database> {
+        self.databases.get(name)
+    }
+
+    pub fn get_database_mut(&mut self, name: &str) -> Option<&mut Database> {
+        self.databases.get_mut(name)
+    }
+
+    // Creating and deleting databases attaches and
+    // detaches them. New databases are kept in memory
+    // until they're saved.
+    pub fn run_query(&mut self, query: Query) -> Result<QueryResult, CoilError> {
+        match (&query.operation, &query.database, &query.table) {
+            (Operation::Create, Some(name), None) => {
+                self.attach(Database::new(name.clone(), DatabaseConfig::default())?)?;
+                return Ok(QueryResult::new(query.operation));
+            },
+            (Operation::Delete, Some(name), None) => {
+                self.detach(&name.clone())?;
+                return Ok(QueryResult::new(query.operation));
+            },
+            _ => {}
+        }
+        let name = query.database.clone().or_else(|| self.current.clone())
+            .ok_or(CoilError::DatabaseDoesntExist)?;
+        self.databases.get_mut(&name).ok_or(CoilError::DatabaseDoesntExist)?.run_query(query)
+    }
+}
+
 #[derive(Debug)]
 pub struct QueryResult<'a> {
     pub operation: Operation,
     pub database: Option<&'a Database>,
     pub table: Option<&'a Table>,
-    pub columns: Option<Vec<&'a Column>>,
+    // The names of the returned columns, in
+    // the order they should be displayed.
+    pub columns: Option<Vec<String>>,
     pub rows: Option<Vec<Row>>,
+    // How many rows a query that changes
+    // rows added, changed or removed.
+    pub affected: Option<usize>,
+    // What an explain query found out.
+    pub explanation: Option<String>,
+    // How many decimal places `print` and `to_csv`
+    // show floats with, if it's fixed.
+    pub float_precision: Option<usize>,
+    // How `print` lays the rows out.
+    pub output_format: OutputFormat
 }
 
 impl<'a> QueryResult<'a> {
     pub fn new(operation: Operation) -> Self {
-        QueryResult{operation: operation, database: None, table: None, columns: None, rows: None}
+        QueryResult{operation: operation, database: None, table: None, columns: None, rows: None, affected: None, explanation: None, float_precision: None, output_format: OutputFormat::Bordered}
     }
 
     pub fn print(&self) {
-        if self.operation != Operation::Get {
-            return;
+        print!("{}", self.to_table_string());
+    }
+
+    // Renders exactly what `print` shows, so it
+    // can be logged or checked somewhere else.
+    pub fn to_table_string(&self) -> String {
+        let mut output = String::new();
+        if let Some(explanation) = &self.explanation {
+            output.push_str(&format!("{}\n", explanation));
+        }
+        if let Some(affected) = self.affected {
+            output.push_str(&format!("{} row{} affected\n", affected, if affected == 1 { "" } else { "s" }));
         }
+        if !self.operation.returns_rows() {
+            return output;
+        }
+        // Results built by hand might not have
+        // any columns or rows to show.
+        let (Some(names), Some(rows)) = (&self.columns, &self.rows) else {
+            output.push_str("No rows\n");
+            return output;
+        };
+        // A `|` in a Markdown cell would end it early,
+        // unless it's escaped.
+        let cell = |text: &str| {
+            if self.output_format == OutputFormat::Markdown {
+                prettytable::Cell::new(&text.replace('|', "\\|"))
+            }
+            else {
+                prettytable::Cell::new(text)
+            }
+        };
         let mut table = prettytable::Table::new();
-        let mut names: Vec<&str> = Vec::new();
         let mut cells: Vec<prettytable::Cell> = Vec::new();
         // Header
-        for column in &self.table.unwrap().columns {
-            names.push(column.name.as_str());
-            cells.push(prettytable::Cell::new(names[names.len() - 1]))
+        for name in names {
+            cells.push(cell(name))
+        }
+        match self.output_format {
+            OutputFormat::Bordered => {
+                table.add_row(prettytable::Row::new(cells));
+            },
+            OutputFormat::Compact => {
+                table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+                table.add_row(prettytable::Row::new(cells));
+            },
+            OutputFormat::Markdown => {
+                // Markdown needs a line of dashes under
+                // the header, and pipes on either side of
+                // every cell, but no lines anywhere else.
+                let format = prettytable::format::FormatBuilder::new()
+                    .column_separator('|')
+                    .borders('|')
+                    .separator(prettytable::format::LinePosition::Title,
+                               prettytable::format::LineSeparator::new('-', '|', '|', '|'))
+                    .padding(1, 1)
+                    .build();
+                table.set_format(format);
+                table.set_titles(prettytable::Row::new(cells));
+            }
         }
-        table.add_row(prettytable::Row::new(cells));
         // Rows
-        for row in self.rows.as_ref().unwrap() {
+        for row in rows {
             let mut values: Vec<prettytable::Cell> = Vec::new();
-            for name in &names {
-                values.push(prettytable::Cell::new(row.get(name).unwrap().to_string().as_str()));
+            for name in names {
+                values.push(cell(&row.get(name).unwrap_or(&FieldValue::None).format(self.float_precision)));
             }
             table.add_row(prettytable::Row::new(values));
         }
 
-        table.printstd();
+        output.push_str(&table.to_string());
+        output
+    }
+
+    // Renders the result as CSV, with a header row of
+    // column names. `none`s are left empty. Like `print`,
+    // this only does anything for queries that return rows,
+    // and a result without any columns is empty too.
+    pub fn to_csv(&self) -> String {
+        if !self.operation.returns_rows() {
+            return String::new();
+        }
+        let Some(names) = &self.columns else {
+            return String::new();
+        };
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let _ = writer.write_record(names);
+        for row in self.rows.as_deref().unwrap_or_default() {
+            let _ = writer.write_record(names.iter().map(|name| {
+                match row.get(name).unwrap_or(&FieldValue::None) {
+                    FieldValue::None => String::new(),
+                    value => value.format(self.float_precision)
+                }
+            }));
+        }
+        String::from_utf8(writer.into_inner().unwrap_or_default()).unwrap_or_default()
+    }
+
+    // Renders the rows as a JSON array of objects keyed
+    // by column name. Like `print`, this only does
+    // anything for queries that return rows.
+    pub fn to_json(&self) -> String {
+        if !self.operation.returns_rows() {
+            return String::new();
+        }
+        let names = self.columns.as_deref().unwrap_or_default();
+        let rows: Vec<JsonRow> = self.rows.as_deref().unwrap_or_default().iter()
+            .map(|row| JsonRow{names: names, row: row})
+            .collect();
+        serde_json::to_string(&rows).unwrap()
+    }
+}
+
+// A row as `QueryResult::to_json` writes it: an object
+// with its fields in column order, holding plain JSON
+// values rather than FieldValue's own serialization.
+struct JsonRow<'a> {
+    names: &'a [String],
+    row: &'a Row
+}
+
+impl Serialize for JsonRow<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.names.len()))?;
+        for name in self.names {
+            let value = match self.row.get(name).unwrap_or(&FieldValue::None) {
+                FieldValue::None => serde_json::Value::Null,
+                FieldValue::Text(text) => serde_json::Value::from(text.as_str()),
+                FieldValue::Integer(number) => serde_json::Value::from(*number),
+                // NaN and infinity become `null`.
+                FieldValue::Float(number) => serde_json::Value::from(*number),
+                FieldValue::Boolean(value) => serde_json::Value::from(*value)
+            };
+            map.serialize_entry(name, &value)?;
+        }
+        map.end()
     }
 }
 
@@ -93,19 +375,116 @@ pub struct DatabaseConfig {
     // size is unknown at compile time, so we'll use
     // this because it's an owned buffer with a type
     // known at compile time :).
-    path: PathBuf
+    path: PathBuf,
+    #[serde(default)]
+    format: StorageFormat,
+    // Whether the database is saved after
+    // every query that changes it.
+    #[serde(default)]
+    pub autosave: bool,
+    // In-memory databases never touch the disk: saving
+    // one is an error (rather than quietly doing nothing,
+    // so nobody thinks their changes were kept), and they
+    // aren't autosaved, whatever `autosave` says.
+    #[serde(default)]
+    pub in_memory: bool,
+    // The most rows a get query can look at before
+    // it's given up on, however many it returns.
+    #[serde(default)]
+    pub max_scan_rows: Option<usize>,
+    // How many decimal places floats are shown with
+    // in query results. `None` shows them as they are.
+    #[serde(default)]
+    pub float_precision: Option<usize>,
+    // How query results are printed.
+    #[serde(default)]
+    pub output_format: OutputFormat
 }
 
 impl DatabaseConfig {
     pub fn new(path: PathBuf) -> Self {
-        DatabaseConfig{path: path}
+        DatabaseConfig{path: path, format: StorageFormat::Json, autosave: false, in_memory: false, max_scan_rows: None, float_precision: None, output_format: OutputFormat::Bordered}
+    }
+
+    pub fn with_format(path: PathBuf, format: StorageFormat) -> Self {
+        DatabaseConfig{path: path, format: format, autosave: false, in_memory: false, max_scan_rows: None, float_precision: None, output_format: OutputFormat::Bordered}
     }
 
     pub fn default() -> Self {
-        let mut config = DatabaseConfig{path: PathBuf::new()};
+        let mut config = DatabaseConfig{path: PathBuf::new(), format: StorageFormat::Json, autosave: false, in_memory: false, max_scan_rows: None, float_precision: None, output_format: OutputFormat::Bordered};
         config.path.push("./");
         config
     }
+
+    pub fn in_memory() -> Self {
+        DatabaseConfig{path: PathBuf::new(), format: StorageFormat::Json, autosave: false, in_memory: true, max_scan_rows: None, float_precision: None, output_format: OutputFormat::Bordered}
+    }
+}
+
+// How a database is written to its file. Binary files
+// are a lot smaller (and quicker to load), but JSON ones
+// can be read and edited by hand.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum StorageFormat {
+    #[default]
+    Json,
+    Binary
+}
+
+// What `Database::merge` does with a table
+// that's in both databases.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergePolicy {
+    // Don't merge anything.
+    Error,
+    // Add the other table's rows to this one, as
+    // long as both have exactly the same columns.
+    Append
+}
+
+// How `QueryResult::print` lays out rows: in a table
+// with borders, in bare columns, or as a markdown table
+// that can be pasted into docs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum OutputFormat {
+    #[default]
+    Bordered,
+    Compact,
+    Markdown
+}
+
+// Binary database files start with this, which is how
+// `Database::from_file` tells them apart from JSON ones.
+const BINARY_MAGIC: &[u8] = b"COIL";
+
+// Splits the path to a database's file into the
+// database's name and the directory it's saved in.
+fn split_path(path: &Path) -> (String, PathBuf) {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    (name, path.parent().map(Path::to_path_buf).unwrap_or_default())
+}
+
+// Database names end up in file paths, so a name with
+// a path separator in it (like `../evil`) could make
+// `save` write somewhere else entirely. Table names
+// are held to the same rules, for consistency.
+fn check_name(name: &str) -> Result<(), CoilError> {
+    if name.is_empty() || name == "." || name == ".."
+        || name.contains(|c: char| c == '/' || c == '\\' || c == '\0') {
+        return Err(CoilError::InvalidName(String::from(name)));
+    }
+    Ok(())
+}
+
+// A name as it'd be written in a query. Names that
+// wouldn't lex back as themselves, like keywords
+// (`where`) or ones with spaces in, are written in
+// backticks.
+fn quote_name(name: &str) -> String {
+    match Lexer::lex(&mut Lexer::new(), String::from(name)).as_deref() {
+        Ok([Token::Identifier(identifier)]) if identifier == name => String::from(name),
+        _ => format!("`{}`", name)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -116,77 +495,625 @@ pub enum CoilError {
     TableDoesntExist,
     DatabaseAlreadyExists,
     DatabaseDoesntExist,
-    MismatchedTypes
+    SaveFailed(String),
+    ImportFailed(String),
+    DeserializationFailed(String),
+    ColumnDoesntExist(String),
+    // A value that doesn't fit its column's type.
+    MismatchedTypes {
+        column: String,
+        expected: FieldType,
+        got: FieldValue
+    },
+    // Values that can't be used together in an
+    // expression, like `"jim" + 1`.
+    IncompatibleTypes,
+    DivisionByZero,
+    ArithmeticOverflow,
+    // Arithmetic whose result isn't a real
+    // number, like `(0 - 8) ^ 0.5`.
+    NotANumber,
+    MultiplePrimaryKeys,
+    DuplicateKey,
+    UniqueViolation(String),
+    NullConstraintViolation(String),
+    ColumnAlreadyExists(String),
+    // Every table needs at least one column.
+    NoColumns,
+    TransactionAlreadyStarted,
+    NoTransaction,
+    // A database or table name that's empty, or that
+    // isn't safe to use in a file path.
+    InvalidName(String),
+    // A REPL command that doesn't exist, or
+    // that was given the wrong arguments.
+    InvalidCommand(String),
+    // A get query had to look at more rows than the
+    // database's `max_scan_rows` allows.
+    ScanLimitExceeded(usize),
+    // A column used after grouping that isn't
+    // one of the columns grouped by.
+    UngroupedColumn(String),
+    // A query ran before a value was bound
+    // to one of its `?` placeholders.
+    UnboundPlaceholder(usize),
+    // A where or having condition that isn't
+    // true or false, like `where 1 + 1`.
+    NonBooleanCondition,
+    // A table joined with itself, whose columns
+    // would all have the same names twice.
+    SelfJoin(String),
+    // `create database` or `delete database` run on a
+    // single database, rather than on a `Workspace`.
+    NeedsWorkspace,
+    // Query text that doesn't lex or parse, as
+    // given to `Query::parse`.
+    InvalidQuery(String),
+    // A value bound to a placeholder the
+    // query doesn't have.
+    NoSuchPlaceholder(usize)
+}
+
+impl CoilError {
+    pub fn message(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for CoilError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CoilError::NotEnoughValues => write!(f, "not enough values"),
+            CoilError::TooManyValues => write!(f, "too many values"),
+            CoilError::TableAlreadyExists => write!(f, "table already exists"),
+            CoilError::TableDoesntExist => write!(f, "table does not exist"),
+            CoilError::DatabaseAlreadyExists => write!(f, "database already exists"),
+            CoilError::DatabaseDoesntExist => write!(f, "database does not exist"),
+            CoilError::SaveFailed(reason) => write!(f, "couldn't save: {}", reason),
+            CoilError::ImportFailed(reason) => write!(f, "couldn't import: {}", reason),
+            CoilError::DeserializationFailed(reason) => write!(f, "couldn't load: {}", reason),
+            CoilError::ColumnDoesntExist(column) => write!(f, "column `{}` does not exist", column),
+            CoilError::MismatchedTypes{column, expected, got} => {
+                write!(f, "`{}` is a {} column, so it can't hold {}", column, expected.name(), got.to_literal())
+            },
+            CoilError::IncompatibleTypes => write!(f, "incompatible types"),
+            CoilError::DivisionByZero => write!(f, "division by zero"),
+            CoilError::ArithmeticOverflow => write!(f, "arithmetic overflow"),
+            CoilError::NotANumber => write!(f, "the result isn't a number"),
+            CoilError::MultiplePrimaryKeys => write!(f, "a table can only have one primary key"),
+            CoilError::DuplicateKey => write!(f, "duplicate primary key"),
+            CoilError::UniqueViolation(column) => write!(f, "`{}` already has that value, and it's unique", column),
+            CoilError::NullConstraintViolation(column) => write!(f, "`{}` can't be none", column),
+            CoilError::ColumnAlreadyExists(column) => write!(f, "column `{}` already exists", column),
+            CoilError::NoColumns => write!(f, "a table needs at least one column"),
+            CoilError::TransactionAlreadyStarted => write!(f, "a transaction has already started"),
+            CoilError::NoTransaction => write!(f, "no transaction has started"),
+            CoilError::InvalidName(name) => write!(f, "`{}` isn't a valid name", name),
+            CoilError::InvalidCommand(message) => write!(f, "{}", message),
+            CoilError::ScanLimitExceeded(max) => write!(f, "the query would look at more than {} rows", max),
+            CoilError::UngroupedColumn(column) => write!(f, "`{}` has to be grouped by, or used in an aggregate", column),
+            CoilError::UnboundPlaceholder(index) => write!(f, "placeholder {} doesn't have a value", index),
+            CoilError::NoSuchPlaceholder(index) => write!(f, "there isn't a placeholder {}", index),
+            CoilError::NonBooleanCondition => write!(f, "the condition isn't true or false"),
+            CoilError::SelfJoin(table) => write!(f, "`{}` can't be joined with itself", table),
+            CoilError::NeedsWorkspace => write!(f, "database statements need a workspace"),
+            CoilError::InvalidQuery(message) => write!(f, "invalid query {}", message)
+        }
+    }
 }
 
+impl std::error::Error for CoilError {}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Database {
     name: String,
     config: DatabaseConfig,
-    tables: Vec<Table>
+    tables: Vec<Table>,
+    // A copy of the tables from when the current
+    // transaction began, for rolling back to.
+    #[serde(skip)]
+    snapshot: Option<Vec<Table>>
 }
 
 impl Database {
-    pub fn new(name: String, config: DatabaseConfig) -> Self {
-        Database{name: name, config: config, tables: Vec::new()}
+    // The name becomes the database's file name, so
+    // one that isn't safe to use there is an error.
+    pub fn new(name: String, config: DatabaseConfig) -> Result<Self, CoilError> {
+        check_name(&name)?;
+        Ok(Database{name: name, config: config, tables: Vec::new(), snapshot: None})
+    }
+
+    pub fn in_memory(name: String) -> Result<Self, CoilError> {
+        Database::new(name, DatabaseConfig::in_memory())
     }
 
+    // Works out the file's format from its contents,
+    // so either kind can be loaded.
     pub fn from_file(path: &Path) -> Result<Self, CoilError> {
-        let file = File::open(path);
-        if file.is_err() {
+        let Ok(contents) = fs::read(path) else {
             return Err(CoilError::DatabaseDoesntExist);
+        };
+        let mut database: Database = match contents.strip_prefix(BINARY_MAGIC) {
+            Some(contents) => bincode::deserialize(contents)
+                .map_err(|error| CoilError::DeserializationFailed(error.to_string()))?,
+            None => serde_json::from_slice(&contents)
+                .map_err(|error| CoilError::DeserializationFailed(error.to_string()))?
+        };
+        // Only which columns are indexed is saved,
+        // so the indexes themselves are rebuilt.
+        for table in &mut database.tables {
+            for column in &mut table.columns {
+                if column.is_indexed {
+                    column.build_index();
+                }
+            }
         }
-        let database = serde_json::from_reader(file.unwrap()).unwrap();
+        // The file could have been edited by hand.
+        check_name(&database.name)?;
         Ok(database)
     }
 
-    pub fn run_query(&mut self, query: Query) -> Option<QueryResult> {
+    pub fn run_query(&mut self, mut query: Query) -> Result<QueryResult, CoilError> {
+        query.resolve_placeholders()?;
+        // A table qualified with another database's
+        // name isn't in this one.
+        if let (Some(database), Some(_)) = (&query.database, &query.table) {
+            if *database != self.name {
+                return Err(CoilError::DatabaseDoesntExist);
+            }
+        }
         let mut result = QueryResult::new(query.operation);
+        result.float_precision = self.config.float_precision;
+        result.output_format = self.config.output_format;
         match result.operation {
             Operation::Get => {
-                let table = self.get_table(query.table?)?;
-                let mut rows;
-                if query.condition.is_some() {
-                    rows = table.get_rows(Some(*(query.condition?)));
+                let table = self.get_table(query.table.ok_or(CoilError::TableDoesntExist)?)
+                    .ok_or(CoilError::TableDoesntExist)?;
+                // A joined query runs against the joined table
+                // instead, which only lives as long as the query
+                // does, so the result can't point at it.
+                let joined: Table;
+                let table = match &query.join {
+                    Some(join) => {
+                        let other = self.get_table(join.table.clone()).ok_or(CoilError::TableDoesntExist)?;
+                        joined = table.join(other, &join.condition, self.config.max_scan_rows)?;
+                        &joined
+                    },
+                    None => {
+                        result.table = Some(table);
+                        table
+                    }
+                };
+                // A single row, with a single boolean
+                // column called "exists".
+                if query.exists {
+                    let exists = match query.condition.as_deref() {
+                        Some(condition) => table.any(condition, self.config.max_scan_rows)?,
+                        None => !table.is_empty()
+                    };
+                    let mut row = Row{columns: IndexMap::new()};
+                    row.columns.insert(String::from("exists"), FieldValue::Boolean(exists));
+                    result.columns = Some(vec![String::from("exists")]);
+                    result.rows = Some(vec![row]);
+                    return Ok(result);
+                }
+                // Grouping, sorting and removing duplicates have to see
+                // every matching row, so the limit and offset are applied
+                // afterwards. Otherwise, they're applied straight away.
+                let grouped = query.group_by.is_some() || !query.aggregates.is_empty() || query.having.is_some();
+                let sees_every_row = grouped || query.order_by.is_some() || query.distinct;
+                let offset = query.offset.unwrap_or(0);
+                let limit = if sees_every_row { None } else { query.limit.map(|limit| limit.saturating_add(offset)) };
+                let mut rows = table.scan_rows(query.condition.as_deref(), limit, self.config.max_scan_rows)?;
+                if !sees_every_row {
+                    rows.drain(..offset.min(rows.len()));
+                }
+                // The names of the columns the rows have from here on.
+                // Grouping leaves just the grouped columns, followed
+                // by the aggregates.
+                let available: Vec<String> = if grouped {
+                    let group_by = query.group_by.unwrap_or_default();
+                    for column in &group_by {
+                        if table.get_column(column).is_none() {
+                            return Err(CoilError::ColumnDoesntExist(column.clone()));
+                        }
+                    }
+                    rows = table.group(rows, &group_by, &query.aggregates)?;
+                    // Unlike `where`, which picks the rows that
+                    // are grouped, `having` picks the groups.
+                    if let Some(having) = &query.having {
+                        let mut kept: Vec<Row> = Vec::new();
+                        for row in rows {
+                            if row.check_condition(having)? {
+                                kept.push(row);
+                            }
+                        }
+                        rows = kept;
+                    }
+                    group_by.into_iter().chain(query.aggregates.iter().map(Aggregate::name)).collect()
                 }
                 else {
-                    rows = table.get_rows(None);
+                    table.columns.iter().map(|column| column.name.clone()).collect()
+                };
+                // A column that exists, but isn't one of the
+                // grouped ones, can't be used after grouping.
+                let missing = |column: &String| {
+                    if grouped && table.get_column(column).is_some() {
+                        CoilError::UngroupedColumn(column.clone())
+                    }
+                    else {
+                        CoilError::ColumnDoesntExist(column.clone())
+                    }
+                };
+                if let Some((column, ascending)) = &query.order_by {
+                    if !available.contains(column) {
+                        return Err(missing(column));
+                    }
+                    rows.sort_by(|a, b| {
+                        let ordering = a.get(column).unwrap().sort_order(b.get(column).unwrap());
+                        if *ascending { ordering } else { ordering.reverse() }
+                    });
                 }
-                result.table = Some(table);
-                result.rows = rows;
+                // Projection happens last, since the condition and
+                // the ordering can use columns that aren't returned.
+                let columns: Vec<String> = match query.projection {
+                    Some(projection) => {
+                        for (i, (column, name)) in projection.iter().enumerate() {
+                            if !available.contains(column) {
+                                return Err(missing(column));
+                            }
+                            // Two different columns can't be
+                            // returned under the same name.
+                            if projection[..i].iter().any(|(other, other_name)| other_name == name && other != column) {
+                                return Err(CoilError::ColumnAlreadyExists(name.clone()));
+                            }
+                        }
+                        rows = rows.iter().map(|row| row.project_as(&projection)).collect();
+                        projection.into_iter().map(|(_, name)| name).collect()
+                    },
+                    None => available
+                };
+                // Rows are compared by their values in column order,
+                // keeping the first of each set of duplicates.
+                if query.distinct {
+                    let mut seen: Vec<Vec<FieldValue>> = Vec::new();
+                    rows.retain(|row| {
+                        let values: Vec<FieldValue> = columns.iter().map(|name| row.get(name).unwrap().clone()).collect();
+                        if seen.contains(&values) {
+                            return false;
+                        }
+                        seen.push(values);
+                        true
+                    });
+                }
+                if sees_every_row {
+                    rows.drain(..offset.min(rows.len()));
+                    rows.truncate(query.limit.unwrap_or(usize::MAX));
+                }
+                result.columns = Some(columns);
+                result.rows = Some(rows);
             },
             Operation::Put => {
-                let _ = self.get_table_mut(query.table?)?.new_row(query.values?);
+                let table = self.get_table_mut(query.table.ok_or(CoilError::TableDoesntExist)?)
+                    .ok_or(CoilError::TableDoesntExist)?;
+                if let Some(file) = query.file {
+                    result.affected = Some(table.import_csv(Path::new(file.as_str()))?);
+                    self.autosave()?;
+                    return Ok(result);
+                }
+                let values = match query.assignments {
+                    Some(assignments) => {
+                        // There isn't a row yet, so the values
+                        // can't use any columns.
+                        let empty = Row{columns: IndexMap::new()};
+                        let mut values: Vec<(String, FieldValue)> = Vec::new();
+                        for (name, expression) in assignments {
+                            values.push((name, empty.evaluate(&expression)?));
+                        }
+                        table.named_row(values)?
+                    },
+                    None => query.values.unwrap_or_default()
+                };
+                if query.update_on_conflict {
+                    table.upsert_row(values)?;
+                }
+                else {
+                    table.new_row(values)?;
+                }
+                result.affected = Some(1);
+                self.autosave()?;
             },
             Operation::Update => {
-                todo!("updating");
+                let table = self.get_table_mut(query.table.ok_or(CoilError::TableDoesntExist)?)
+                    .ok_or(CoilError::TableDoesntExist)?;
+                let assignments = query.assignments.unwrap_or_default();
+                result.affected = Some(table.update_rows(&assignments, query.condition.as_deref())?);
+                self.autosave()?;
             },
             Operation::Create => {
                 if let Some(table) = query.table {
-                    result.table = Some(self.new_table(table, query.columns?).unwrap());
+                    self.new_table(table.clone(), query.columns.unwrap_or_default())?;
+                    self.autosave()?;
+                    result.table = self.get_table(table);
+                }
+                else {
+                    // Only a workspace has somewhere
+                    // to put a new database.
+                    return Err(CoilError::NeedsWorkspace);
                 }
-                todo!("creating databases");
             },
             Operation::Delete => {
-                todo!("deletion");
+                if query.delete_rows {
+                    let table = self.get_table_mut(query.table.ok_or(CoilError::TableDoesntExist)?)
+                        .ok_or(CoilError::TableDoesntExist)?;
+                    result.affected = Some(table.delete_rows(query.condition.as_deref())?);
+                    self.autosave()?;
+                }
+                else if let Some(table) = query.table {
+                    self.delete_table(&table)?;
+                    self.autosave()?;
+                }
+                else {
+                    return Err(CoilError::NeedsWorkspace);
+                }
+            },
+            Operation::Alter => {
+                let name = query.table.ok_or(CoilError::TableDoesntExist)?;
+                if let Some(Alteration::RenameTable(new_name)) = query.alteration {
+                    self.rename_table(&name, new_name)?;
+                    self.autosave()?;
+                    return Ok(result);
+                }
+                let table = self.get_table_mut(name).ok_or(CoilError::TableDoesntExist)?;
+                match query.alteration {
+                    Some(Alteration::AddColumn(column)) => table.add_column(column)?,
+                    Some(Alteration::DropColumn(name)) => table.drop_column(&name)?,
+                    Some(Alteration::RenameColumn(name, new_name)) => table.rename_column(&name, new_name)?,
+                    _ => {}
+                }
+                self.autosave()?;
+            },
+            Operation::Truncate => {
+                let table = self.get_table_mut(query.table.ok_or(CoilError::TableDoesntExist)?)
+                    .ok_or(CoilError::TableDoesntExist)?;
+                result.affected = Some(table.truncate());
+                self.autosave()?;
+            },
+            Operation::Describe => {
+                let table = self.get_table(query.table.ok_or(CoilError::TableDoesntExist)?)
+                    .ok_or(CoilError::TableDoesntExist)?;
+                let mut rows: Vec<Row> = Vec::new();
+                for column in &table.columns {
+                    let mut row = Row{columns: IndexMap::new()};
+                    row.columns.insert(String::from("column"), FieldValue::Text(column.name.clone()));
+                    row.columns.insert(String::from("type"), FieldValue::Text(String::from(column.field_type.name())));
+                    row.columns.insert(String::from("modifiers"), FieldValue::Text(column.modifiers()));
+                    rows.push(row);
+                }
+                result.table = Some(table);
+                result.columns = Some(vec![String::from("column"), String::from("type"), String::from("modifiers")]);
+                result.rows = Some(rows);
+            },
+            Operation::Begin => self.begin()?,
+            Operation::Commit => {
+                self.commit()?;
+                self.autosave()?;
+            },
+            Operation::Rollback => self.rollback()?,
+            Operation::Explain => {
+                let explained = query.explained.ok_or(CoilError::IncompatibleTypes)?;
+                result.explanation = Some(format!("{}\n\n{:#?}", self.plan(&explained)?.join("\n"), explained));
             },
+            Operation::Show => {
+                let mut rows: Vec<Row> = Vec::new();
+                for name in self.table_names() {
+                    let mut row = Row{columns: IndexMap::new()};
+                    row.columns.insert(String::from("table"), FieldValue::Text(String::from(name)));
+                    rows.push(row);
+                }
+                result.columns = Some(vec![String::from("table")]);
+                result.rows = Some(rows);
+            }
         }
 
-        Some(result)
+        Ok(result)
     }
 
     pub fn new_table(&mut self, name: String, columns: Vec<Column>) -> Result<&mut Table, CoilError> {
+        check_name(&name)?;
         for table in &self.tables {
             if table.name == name {
                 return Err(CoilError::TableAlreadyExists);
             }
         }
+        if columns.is_empty() {
+            return Err(CoilError::NoColumns);
+        }
+        if columns.iter().filter(|column| column.is_primary).count() > 1 {
+            return Err(CoilError::MultiplePrimaryKeys);
+        }
+        for (i, column) in columns.iter().enumerate() {
+            column.check_definition()?;
+            if columns[..i].iter().any(|other| other.name == column.name) {
+                return Err(CoilError::ColumnAlreadyExists(column.name.clone()));
+            }
+        }
         self.tables.push(Table::new(name, columns));
 
         let new_table_index = self.tables.len() - 1;
         Ok(&mut self.tables[new_table_index])
     }
 
+    // Saves the database if its config asks for that after
+    // every change. Changes made in a transaction aren't
+    // saved until it's committed.
+    fn autosave(&self) -> Result<(), CoilError> {
+        if self.config.autosave && !self.config.in_memory && !self.in_transaction() {
+            return self.save();
+        }
+        Ok(())
+    }
+
+    // Transactions work by copying every table when they
+    // begin, which is simple but not cheap for big tables.
+    // They can't be nested.
+    pub fn begin(&mut self) -> Result<(), CoilError> {
+        if self.snapshot.is_some() {
+            return Err(CoilError::TransactionAlreadyStarted);
+        }
+        self.snapshot = Some(self.tables.clone());
+        Ok(())
+    }
+
+    pub fn commit(&mut self) -> Result<(), CoilError> {
+        self.snapshot.take().ok_or(CoilError::NoTransaction)?;
+        Ok(())
+    }
+
+    // Undoes every change made since the transaction began.
+    pub fn rollback(&mut self) -> Result<(), CoilError> {
+        self.tables = self.snapshot.take().ok_or(CoilError::NoTransaction)?;
+        Ok(())
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.snapshot.is_some()
+    }
+
+    pub fn delete_table(&mut self, name: &str) -> Result<(), CoilError> {
+        let Some(index) = self.tables.iter().position(|table| table.name == name) else {
+            return Err(CoilError::TableDoesntExist);
+        };
+        self.tables.remove(index);
+        Ok(())
+    }
+
+    pub fn rename_table(&mut self, name: &str, new_name: String) -> Result<(), CoilError> {
+        check_name(&new_name)?;
+        if self.get_table(new_name.clone()).is_some() {
+            return Err(CoilError::TableAlreadyExists);
+        }
+        let table = self.get_table_mut(String::from(name)).ok_or(CoilError::TableDoesntExist)?;
+        table.name = new_name;
+        Ok(())
+    }
+
+    // Moves `other`'s tables into this database. What
+    // happens to a table both databases have is up to
+    // `policy`. Nothing is merged unless everything can
+    // be, so an error leaves the database as it was.
+    pub fn merge(&mut self, other: Database, policy: MergePolicy) -> Result<(), CoilError> {
+        for table in &other.tables {
+            if let Some(existing) = self.get_table(table.name.clone()) {
+                // Tables with the same name have the same
+                // columns if their create queries match.
+                if policy == MergePolicy::Error || existing.to_ddl() != table.to_ddl() {
+                    return Err(CoilError::TableAlreadyExists);
+                }
+            }
+        }
+
+        // The rows being appended could still clash
+        // with the keys already in a table.
+        let backup = self.tables.clone();
+        for table in other.tables {
+            let Some(existing) = self.tables.iter_mut().find(|existing| existing.name == table.name) else {
+                self.tables.push(table);
+                continue;
+            };
+            for i in 0..table.row_count() {
+                let values: Vec<FieldValue> = table.columns.iter().map(|column| column.rows[i].clone()).collect();
+                if let Err(error) = existing.new_row(values) {
+                    self.tables = backup;
+                    return Err(error);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // In the order they were created.
+    pub fn table_names(&self) -> Vec<&str> {
+        self.tables.iter().map(|table| table.name.as_str()).collect()
+    }
+
+    pub fn tables(&self) -> impl Iterator<Item = &Table> {
+        self.tables.iter()
+    }
+
+    pub fn table_count(&self) -> usize {
+        self.tables.len()
+    }
+
+    // The steps a query would take, in order, as
+    // shown by `explain`. Checks that the tables the
+    // query reads exist, but doesn't read them.
+    fn plan(&self, query: &Query) -> Result<Vec<String>, CoilError> {
+        let mut steps: Vec<String> = Vec::new();
+        let condition = query.condition.as_deref();
+        match query.operation {
+            Operation::Get => {
+                let table = self.get_table(query.table.clone().ok_or(CoilError::TableDoesntExist)?)
+                    .ok_or(CoilError::TableDoesntExist)?;
+                match &query.join {
+                    // The joined table is built from scratch,
+                    // so it never has any indexes.
+                    Some(join) => {
+                        let other = self.get_table(join.table.clone()).ok_or(CoilError::TableDoesntExist)?;
+                        steps.push(format!("join `{}` with `{}`, comparing every pair of rows", table.name, other.name));
+                        steps.push(String::from("full scan of the joined rows"));
+                    },
+                    None => steps.push(table.plan(condition))
+                }
+                if query.exists {
+                    steps.push(String::from("stop at the first matching row"));
+                }
+                if let Some(group_by) = &query.group_by {
+                    steps.push(format!("group by `{}`", group_by.join("`, `")));
+                }
+                if !query.aggregates.is_empty() {
+                    let names: Vec<String> = query.aggregates.iter().map(Aggregate::name).collect();
+                    steps.push(format!("aggregate into `{}`", names.join("`, `")));
+                }
+                if query.having.is_some() {
+                    steps.push(String::from("keep the groups that satisfy `having`"));
+                }
+                if let Some((column, ascending)) = &query.order_by {
+                    steps.push(format!("sort by `{}` {}", column, if *ascending { "ascending" } else { "descending" }));
+                }
+                if query.distinct {
+                    steps.push(String::from("remove duplicate rows"));
+                }
+                if let Some(offset) = query.offset {
+                    steps.push(format!("skip the first {} rows", offset));
+                }
+                if let Some(limit) = query.limit {
+                    steps.push(format!("keep at most {} rows", limit));
+                }
+            },
+            Operation::Delete if query.delete_rows => {
+                let table = self.get_table(query.table.clone().ok_or(CoilError::TableDoesntExist)?)
+                    .ok_or(CoilError::TableDoesntExist)?;
+                steps.push(table.plan(condition));
+                steps.push(String::from("remove the matching rows"));
+            },
+            Operation::Update => {
+                let table = self.get_table(query.table.clone().ok_or(CoilError::TableDoesntExist)?)
+                    .ok_or(CoilError::TableDoesntExist)?;
+                steps.push(table.plan(condition));
+                steps.push(String::from("update the matching rows"));
+            },
+            _ => steps.push(String::from("no rows are read"))
+        }
+        Ok(steps)
+    }
+
+    // A create query for every table, one per line,
+    // which together rebuild the database's structure.
+    pub fn to_ddl(&self) -> String {
+        self.tables.iter().map(|table| format!("{};\n", table.to_ddl())).collect()
+    }
+
     pub fn get_table<'a>(&'a self, name: String) -> Option<&'a Table> {
         for table in &self.tables {
             if table.name == name {
@@ -205,13 +1132,62 @@ impl Database {
         None
     }
 
-    pub fn save(&self) -> Result<usize, std::io::Error> {
-        let mut file = File::create((*self.config.path).with_file_name(self.name.as_str()))?;
-        file.write(serde_json::to_string(self).unwrap().as_bytes())
+    // The database's file is named after it, so the
+    // next save writes to a new file, and the old
+    // one is left alone.
+    pub fn rename(&mut self, new_name: String) -> Result<(), CoilError> {
+        check_name(&new_name)?;
+        self.name = new_name;
+        Ok(())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Points the database at the file at `path`, taking
+    // its name from the file's, so it saves there.
+    fn move_to(&mut self, path: &Path) -> Result<(), CoilError> {
+        let (name, directory) = split_path(path);
+        check_name(&name)?;
+        self.name = name;
+        self.config.path = directory;
+        Ok(())
+    }
+
+    // A database lives in a file named after it, inside
+    // the directory given by its config's `path`. So a
+    // database called "business" with a path of "./data/"
+    // is saved to "./data/business".
+    pub fn file_path(&self) -> PathBuf {
+        self.config.path.join(self.name.as_str())
+    }
+
+    // Creates the config's directory if it doesn't exist yet.
+    pub fn save(&self) -> Result<(), CoilError> {
+        if self.config.in_memory {
+            return Err(CoilError::SaveFailed(format!("`{}` is an in-memory database", self.name)));
+        }
+        fs::create_dir_all(&self.config.path).map_err(|error| CoilError::SaveFailed(
+            format!("couldn't create directory {}: {}", self.config.path.display(), error)))?;
+        let path = self.file_path();
+        let contents = match self.config.format {
+            StorageFormat::Json => serde_json::to_vec(self).unwrap(),
+            StorageFormat::Binary => {
+                let mut contents = BINARY_MAGIC.to_vec();
+                contents.extend(bincode::serialize(self).map_err(|error| CoilError::SaveFailed(
+                    format!("couldn't serialize {}: {}", self.name, error)))?);
+                contents
+            }
+        };
+        let mut file = File::create(&path).map_err(|error| CoilError::SaveFailed(
+            format!("couldn't create {}: {}", path.display(), error)))?;
+        file.write_all(&contents).map_err(|error| CoilError::SaveFailed(
+            format!("couldn't write {}: {}", path.display(), error)))
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Table {
     name: String,
     columns: Vec<Column>
@@ -222,127 +1198,1123 @@ impl Table {
         Table{name: name, columns: columns}
     }
 
-    pub fn new_row(&mut self, values: Vec<FieldValue>) -> Option<CoilError> {
-        if values.len() > self.columns.len() {
-            return Some(CoilError::TooManyValues);
-        }
-        else if values.len() < self.columns.len() {
-            return Some(CoilError::NotEnoughValues);
-        }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // The create query that would make this table,
+    // without any of its rows.
+    pub fn to_ddl(&self) -> String {
+        let columns: Vec<String> = self.columns.iter().map(|column| column.to_ddl()).collect();
+        format!("create table {} [{}]", quote_name(&self.name), columns.join(", "))
+    }
+
+    // Every column has the same number of rows, so the
+    // first one is as good as any. A table without any
+    // columns can't have any rows either.
+    pub fn row_count(&self) -> usize {
+        self.columns.first().map_or(0, |column| column.rows.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.row_count() == 0
+    }
+
+    pub fn new_row(&mut self, values: Vec<FieldValue>) -> Result<(), CoilError> {
+        let mut values = self.complete_row(values)?;
+        self.generate_auto_values(&mut values, &mut self.last_autos());
 
+        // Every value is checked before any are added,
+        // so a bad row can't leave the columns with
+        // different numbers of rows.
+        for i in 0..values.len() {
+            self.columns[i].check_constraints(&values[i])?;
+        }
         for i in 0..values.len() {
             let _ = self.columns[i].push(values[i].clone());
         }
 
-        None
+        Ok(())
     }
 
-    pub fn get_rows(&self, condition: Option<Expression>) -> Option<Vec<Row>> {
-        let mut rows: Vec<Row> = Vec::new();
-        // I figured it's better to branch once before
-        // the loop than to branch and unwrap on every
-        // iteration. Unfortunately, this does end up
-        // looking very ugly!
-        if let Some(row_condition) = condition {
-            for i in 0..self.columns[0].rows.len() {
-                let row = Row::from_columns(&self.columns, i);
-                if row.check_condition(&row_condition) {
-                    rows.push(row);
+    // Like `new_row`, except that if the row has the same
+    // primary key or unique value as a row that's already
+    // there, that row is overwritten instead. Tables without
+    // primary or unique columns can't have conflicts, so
+    // for them this is the same as `new_row`.
+    pub fn upsert_row(&mut self, values: Vec<FieldValue>) -> Result<(), CoilError> {
+        let mut values = self.complete_row(values)?;
+        self.generate_auto_values(&mut values, &mut self.last_autos());
+
+        // All of the row's keys have to point at the same
+        // row, or overwriting it would make a duplicate.
+        let mut target: Option<usize> = None;
+        for (column, value) in self.columns.iter().zip(&values) {
+            if !(column.is_primary || column.is_unique) || *value == FieldValue::None {
+                continue;
+            }
+            if let Some(row) = column.rows.iter().position(|existing| existing == value) {
+                if target.is_some_and(|target| target != row) {
+                    return Err(column.duplicate_error());
                 }
+                target = Some(row);
             }
         }
-        else {
-            for i in 0..self.columns[0].rows.len() {
-                let row = Row::from_columns(&self.columns, i);
-                rows.push(row);
+        let Some(target) = target else {
+            return self.new_row(values);
+        };
+
+        for (column, value) in self.columns.iter().zip(&values) {
+            // The only duplicates left are in the row
+            // that's about to be overwritten.
+            match column.check_constraints(value) {
+                Err(CoilError::DuplicateKey) | Err(CoilError::UniqueViolation(_)) | Ok(()) => {},
+                Err(error) => { return Err(error); }
             }
         }
+        for (column, value) in self.columns.iter_mut().zip(values) {
+            let _ = column.set(target, value);
+        }
 
-        Some(rows)
+        Ok(())
     }
-}
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct Row {
-    columns: HashMap<String, FieldValue>
-}
+    // Adds several rows at once, returning how many were
+    // added. Either every row is added or none are. Rather
+    // than scanning the key columns for every row like
+    // `new_row` does, their values are gathered into sets
+    // up front, which makes big inserts a lot quicker.
+    pub fn new_rows(&mut self, rows: Vec<Vec<FieldValue>>) -> Result<usize, CoilError> {
+        let rows = self.check_rows(rows).map_err(|(_, error)| error)?;
+        let row_count = rows.len();
+        self.push_rows(rows);
+        Ok(row_count)
+    }
 
-impl Row {
-    pub fn from_columns(columns: &Vec<Column>, index: usize) -> Self {
-        let mut row = Row{columns: HashMap::new()};
-        for column in columns {
-            row.columns.insert(column.name.clone(), column.rows[index].clone());
+    // Completes and checks rows for `new_rows`, without
+    // adding any of them. Errors come with the index of
+    // the row that caused them.
+    fn check_rows(&self, rows: Vec<Vec<FieldValue>>) -> Result<Vec<Vec<FieldValue>>, (usize, CoilError)> {
+        let mut keys: Vec<Option<HashSet<IndexKey>>> = self.columns.iter().map(|column| {
+            if column.is_primary || column.is_unique {
+                Some(column.rows.iter().map(|value| value.index_key()).collect())
+            }
+            else {
+                None
+            }
+        }).collect();
+        let mut last_autos = self.last_autos();
+
+        let mut checked: Vec<Vec<FieldValue>> = Vec::new();
+        for (i, values) in rows.into_iter().enumerate() {
+            let mut values = self.complete_row(values).map_err(|error| (i, error))?;
+            self.generate_auto_values(&mut values, &mut last_autos);
+            for (j, column) in self.columns.iter().enumerate() {
+                column.check_value(&values[j]).map_err(|error| (i, error))?;
+                if let Some(keys) = &mut keys[j] {
+                    if values[j] != FieldValue::None && !keys.insert(values[j].index_key()) {
+                        return Err((i, column.duplicate_error()));
+                    }
+                }
+            }
+            checked.push(values);
         }
-        row
+
+        Ok(checked)
     }
 
-    pub fn get(&self, field: &str) -> Option<&FieldValue> {
-        self.columns.get(field)
+    // Adds rows that have already been checked.
+    fn push_rows(&mut self, rows: Vec<Vec<FieldValue>>) {
+        for values in rows {
+            for (column, value) in self.columns.iter_mut().zip(values) {
+                let _ = column.push(value);
+            }
+        }
+    }
+
+    fn last_autos(&self) -> Vec<i64> {
+        self.columns.iter().map(|column| column.last_auto).collect()
     }
 
-    // TODO: this function cannot handle nested expressions...
-    pub fn check_condition(&self, condition: &Expression) -> bool {
-        let l_operand = condition.l_operand.as_ref().unwrap();
-        let r_operand = condition.r_operand.as_ref().unwrap();
-        let mut l_value;
-        let mut r_value;
-        // Resolve identifier values and convert
-        // ExpressionTypes into FieldValues
-        if let ExpressionType::Identifier(identifier) = &l_operand.expression_type {
-            // TODO: this function should actually
-            // return a Result<bool> to handle errors
-            l_value = self.get(identifier.as_str()).unwrap().clone();
+    // Gives the auto columns that were left `none` the
+    // next value after the one in `last_autos` (which
+    // has an entry for every column), keeping it up to
+    // date with the row's values.
+    fn generate_auto_values(&self, values: &mut [FieldValue], last_autos: &mut [i64]) {
+        for i in 0..values.len() {
+            if !self.columns[i].is_auto {
+                continue;
+            }
+            if values[i] == FieldValue::None {
+                values[i] = FieldValue::Integer(last_autos[i] + 1);
+            }
+            if let Some(number) = values[i].as_float() {
+                last_autos[i] = last_autos[i].max(number.floor() as i64);
+            }
         }
-        else {
-            l_value = FieldValue::from_expression_type(l_operand.expression_type.clone());
+    }
+
+    // Puts values given by column name into column order.
+    // Columns without a value get their default, or `none`
+    // (which auto columns fill in when the row's added).
+    // If a column's named more than once, the last one wins.
+    pub fn named_row(&self, values: Vec<(String, FieldValue)>) -> Result<Vec<FieldValue>, CoilError> {
+        let mut row: Vec<FieldValue> = self.columns.iter()
+            .map(|column| column.default.clone().unwrap_or(FieldValue::None))
+            .collect();
+        for (name, value) in values {
+            let i = self.columns.iter().position(|column| column.name == name)
+                .ok_or(CoilError::ColumnDoesntExist(name))?;
+            row[i] = value;
         }
-        if let ExpressionType::Identifier(identifier) = &r_operand.expression_type {
-            r_value = self.get(identifier.as_str()).unwrap().clone();
+        Ok(row)
+    }
+
+    // Fills in the values an insert left out. Auto
+    // columns are left `none` for now.
+    fn complete_row(&self, mut values: Vec<FieldValue>) -> Result<Vec<FieldValue>, CoilError> {
+        if values.len() > self.columns.len() {
+            return Err(CoilError::TooManyValues);
         }
-        else {
-            r_value = FieldValue::from_expression_type(r_operand.expression_type.clone());
+        // When values are left out, auto columns are assumed
+        // to be among them (so giving an auto column a value
+        // means giving every column one). The rest are filled
+        // in order, with trailing gaps taking their defaults.
+        if values.len() < self.columns.len() {
+            let mut given = values.into_iter();
+            values = Vec::new();
+            for column in &self.columns {
+                if column.is_auto {
+                    values.push(FieldValue::None);
+                    continue;
+                }
+                match given.next().or_else(|| column.default.clone()) {
+                    Some(value) => values.push(value),
+                    None => { return Err(CoilError::NotEnoughValues); }
+                }
+            }
+            if given.next().is_some() {
+                return Err(CoilError::TooManyValues);
+            }
         }
 
-        match condition.expression_type {
-            ExpressionType::Equal => l_value == r_value,
-            ExpressionType::NotEqual => l_value != r_value,
-            ExpressionType::LessThan => l_value < r_value,
-            ExpressionType::LessThanOrEqual => l_value <= r_value,
-            ExpressionType::GreaterThan => l_value > r_value,
-            ExpressionType::GreaterThanOrEqual => l_value >= r_value,
-            // ExpressionType::And => l_value && r_value,
-            // ExpressionType::Or => l_value || r_value,
-            // ExpressionType::Xor => l_value != r_value,
-            _ => false
-        }
+        Ok(values)
+    }
+
+    // Adds a column to the end of the table. The rows that
+    // are already there get the column's default (or `none`,
+    // or the next number for an auto column), so that every
+    // column still has the same number of rows.
+    pub fn add_column(&mut self, mut column: Column) -> Result<(), CoilError> {
+        if self.get_column(&column.name).is_some() {
+            return Err(CoilError::ColumnAlreadyExists(column.name));
+        }
+        if column.is_primary && self.columns.iter().any(|column| column.is_primary) {
+            return Err(CoilError::MultiplePrimaryKeys);
+        }
+        column.check_definition()?;
+
+        let row_count = self.row_count();
+        for i in 0..row_count {
+            let value = if column.is_auto {
+                FieldValue::Integer(i as i64 + 1)
+            }
+            else {
+                column.default.clone().unwrap_or(FieldValue::None)
+            };
+            column.check_constraints(&value)?;
+            column.rows.push(value);
+        }
+        if column.is_auto {
+            column.last_auto = row_count as i64;
+        }
+        self.columns.push(column);
+
+        Ok(())
+    }
+
+    // Reads rows from a CSV file whose header names the
+    // table's columns (in any order), returning how many
+    // were added. Empty fields are `none`s. Like with
+    // `new_rows`, either every row is added or none are.
+    pub fn import_csv(&mut self, path: &Path) -> Result<usize, CoilError> {
+        let failed = |line: u64, message: String| CoilError::ImportFailed(format!("line {}: {}", line, message));
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_path(path)
+            .map_err(|error| CoilError::ImportFailed(format!("couldn't open {}: {}", path.display(), error)))?;
+
+        // Which field each column's values come from.
+        let header = reader.headers().map_err(|error| failed(1, error.to_string()))?.clone();
+        if header.len() != self.columns.len() {
+            return Err(failed(1, format!("expected {} columns, found {}", self.columns.len(), header.len())));
+        }
+        let mut fields: Vec<usize> = Vec::new();
+        for column in &self.columns {
+            let Some(field) = header.iter().position(|name| name == column.name) else {
+                return Err(failed(1, format!("missing column `{}`", column.name)));
+            };
+            fields.push(field);
+        }
+
+        let mut lines: Vec<u64> = Vec::new();
+        let mut rows: Vec<Vec<FieldValue>> = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|error| {
+                let line = error.position().map_or(0, |position| position.line());
+                failed(line, error.to_string())
+            })?;
+            let line = record.position().map_or(0, |position| position.line());
+            if record.len() != self.columns.len() {
+                return Err(failed(line, format!("expected {} fields, found {}", self.columns.len(), record.len())));
+            }
+            let mut values: Vec<FieldValue> = Vec::new();
+            for (column, field) in self.columns.iter().zip(&fields) {
+                let text = &record[*field];
+                let Some(value) = column.field_type.parse_field_value(text) else {
+                    return Err(failed(line, format!("`{}` isn't a valid value for `{}`", text, column.name)));
+                };
+                values.push(value);
+            }
+            lines.push(line);
+            rows.push(values);
+        }
+
+        let rows = self.check_rows(rows).map_err(|(i, error)| failed(lines[i], error.message()))?;
+        let row_count = rows.len();
+        self.push_rows(rows);
+        Ok(row_count)
+    }
+
+    // Removes a column, along with everything in it.
+    // The table's last column can't be dropped.
+    pub fn drop_column(&mut self, name: &str) -> Result<(), CoilError> {
+        let Some(index) = self.columns.iter().position(|column| column.name == name) else {
+            return Err(CoilError::ColumnDoesntExist(String::from(name)));
+        };
+        if self.columns.len() == 1 {
+            return Err(CoilError::NoColumns);
+        }
+        self.columns.remove(index);
+        Ok(())
+    }
+
+    // Only the name changes, so the column
+    // keeps its rows and its index. Renaming a
+    // column to its own name doesn't do anything.
+    pub fn rename_column(&mut self, name: &str, new_name: String) -> Result<(), CoilError> {
+        if new_name != name && self.get_column(&new_name).is_some() {
+            return Err(CoilError::ColumnAlreadyExists(new_name));
+        }
+        let Some(column) = self.columns.iter_mut().find(|column| column.name == name) else {
+            return Err(CoilError::ColumnDoesntExist(String::from(name)));
+        };
+        column.name = new_name;
+        Ok(())
+    }
+
+    pub fn get_column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    // Like in SQL, `none`s are left out of aggregates
+    // over a column: `count(col)` only counts the rows
+    // that have a value, and the others only look at
+    // those values. Without any, every aggregate but
+    // `count` is `none`. `count(*)` counts every row.
+    // `count(distinct col)` counts 1 and 1.0 as the
+    // same value, like `=` does.
+    pub fn aggregate(&self, rows: &[Row], aggregate: &Aggregate) -> Result<FieldValue, CoilError> {
+        let Some(name) = &aggregate.column else {
+            // Only `count(*)` gets past the parser without a column.
+            return Ok(FieldValue::Integer(rows.len() as i64));
+        };
+        let column = self.get_column(name).ok_or_else(|| CoilError::ColumnDoesntExist(name.clone()))?;
+        let values: Vec<&FieldValue> = rows.iter()
+            .map(|row| row.get(name).unwrap())
+            .filter(|value| **value != FieldValue::None)
+            .collect();
+
+        match aggregate.function {
+            AggregateFunction::Count => Ok(FieldValue::Integer(values.len() as i64)),
+            AggregateFunction::CountDistinct => {
+                let distinct: HashSet<IndexKey> = values.iter().map(|value| value.index_key()).collect();
+                Ok(FieldValue::Integer(distinct.len() as i64))
+            },
+            AggregateFunction::Sum | AggregateFunction::Average => {
+                if column.field_type != FieldType::Number {
+                    let got = values.first().map_or(FieldValue::None, |value| (*value).clone());
+                    return Err(CoilError::MismatchedTypes{column: name.clone(),
+                                                          expected: FieldType::Number,
+                                                          got: got});
+                }
+                if values.is_empty() {
+                    return Ok(FieldValue::None);
+                }
+                let mut sum = FieldValue::Integer(0);
+                for value in &values {
+                    sum = FieldValue::arithmetic(&ExpressionType::Add, sum, (*value).clone())?;
+                }
+                if aggregate.function == AggregateFunction::Sum {
+                    return Ok(sum);
+                }
+                FieldValue::arithmetic(&ExpressionType::Divide,
+                                       FieldValue::Float(sum.as_float().unwrap()),
+                                       FieldValue::Integer(values.len() as i64))
+            },
+            AggregateFunction::Minimum | AggregateFunction::Maximum => {
+                let wanted = if aggregate.function == AggregateFunction::Minimum {
+                    Ordering::Less
+                }
+                else {
+                    Ordering::Greater
+                };
+                let mut extreme = FieldValue::None;
+                for (i, value) in values.into_iter().enumerate() {
+                    if i == 0 || value.partial_cmp(&extreme) == Some(wanted) {
+                        extreme = value.clone();
+                    }
+                }
+                Ok(extreme)
+            }
+        }
+    }
+
+    // Splits `rows` into groups that have the same values
+    // in the `group_by` columns, in the order each group
+    // first turns up, and folds each group into a row of
+    // those values followed by the aggregates. Without
+    // any `group_by` columns, every row is in one group,
+    // which is still there if there aren't any rows.
+    pub fn group(&self, rows: Vec<Row>, group_by: &[String], aggregates: &[Aggregate]) -> Result<Vec<Row>, CoilError> {
+        let mut groups: IndexMap<Vec<IndexKey>, Vec<Row>> = IndexMap::new();
+        if group_by.is_empty() {
+            groups.insert(Vec::new(), Vec::new());
+        }
+        for row in rows {
+            let key = group_by.iter().map(|name| row.get(name).map_or(IndexKey::None, FieldValue::index_key)).collect();
+            groups.entry(key).or_default().push(row);
+        }
+
+        let mut grouped: Vec<Row> = Vec::new();
+        for rows in groups.into_values() {
+            let mut row = Row{columns: IndexMap::new()};
+            for name in group_by {
+                let value = rows.first().and_then(|first| first.get(name)).cloned().unwrap_or(FieldValue::None);
+                row.columns.insert(name.clone(), value);
+            }
+            for aggregate in aggregates {
+                row.columns.insert(aggregate.name(), self.aggregate(&rows, aggregate)?);
+            }
+            grouped.push(row);
+        }
+        Ok(grouped)
+    }
+
+    // Pairs up every row of this table with every row of
+    // `other`, keeping the pairs that satisfy `condition`.
+    // The result is a new table whose columns are named
+    // `table.column`, so that columns sharing a name in
+    // the two tables don't clash. That can't work for a
+    // table joined with itself, so that's an error. Every
+    // pair counts as a scanned row, so more than
+    // `max_scanned` pairs is an error too.
+    pub fn join(&self, other: &Table, condition: &Expression, max_scanned: Option<usize>) -> Result<Table, CoilError> {
+        if self.name == other.name {
+            return Err(CoilError::SelfJoin(self.name.clone()));
+        }
+        let mut columns: Vec<Column> = Vec::new();
+        for table in [self, other] {
+            for column in &table.columns {
+                columns.push(Column::new(format!("{}.{}", table.name, column.name), column.field_type.clone()));
+            }
+        }
+        let mut joined = Table::new(format!("{}+{}", self.name, other.name), columns);
+
+        let mut scanned: usize = 0;
+        for i in 0..self.row_count() {
+            for j in 0..other.row_count() {
+                scanned += 1;
+                if let Some(max_scanned) = max_scanned.filter(|max_scanned| scanned > *max_scanned) {
+                    return Err(CoilError::ScanLimitExceeded(max_scanned));
+                }
+                let mut row = Row{columns: IndexMap::new()};
+                for (table, index) in [(self, i), (other, j)] {
+                    for column in &table.columns {
+                        row.columns.insert(format!("{}.{}", table.name, column.name), column.rows[index].clone());
+                    }
+                }
+                if !row.check_condition(condition)? {
+                    continue;
+                }
+                for column in &mut joined.columns {
+                    let value = row.columns.shift_remove(&column.name)
+                        .ok_or_else(|| CoilError::ColumnDoesntExist(column.name.clone()))?;
+                    column.rows.push(value);
+                }
+            }
+        }
+
+        Ok(joined)
+    }
+
+    // Indexes a column, so that conditions comparing it
+    // for equality with a value don't have to look at
+    // every row. Other kinds of conditions (`<`, `like`,
+    // etc.) still scan the whole table.
+    pub fn create_index(&mut self, column: &str) -> Result<(), CoilError> {
+        let Some(column) = self.columns.iter_mut().find(|c| c.name == column) else {
+            return Err(CoilError::ColumnDoesntExist(String::from(column)));
+        };
+        column.build_index();
+        Ok(())
+    }
+
+    // The names of the columns that have indexes.
+    pub fn indexed_columns(&self) -> Vec<&str> {
+        self.columns.iter()
+            .filter(|column| column.is_indexed)
+            .map(|column| column.name.as_str())
+            .collect()
+    }
+
+    // The rows an index says could satisfy `condition`, if
+    // the condition is `column = value` (or has it on either
+    // side of an `and`) and the column is indexed. The
+    // condition still has to be checked against these rows.
+    fn indexed_rows(&self, condition: &Expression) -> Option<Vec<usize>> {
+        let (column, key) = self.index_lookup(condition)?;
+        Some(column.index.as_ref()?.get(&key).cloned().unwrap_or_default())
+    }
+
+    // The indexed column and key `indexed_rows` uses
+    // for `condition`.
+    fn index_lookup(&self, condition: &Expression) -> Option<(&Column, IndexKey)> {
+        let (l_operand, r_operand) = condition.operands()?;
+        match condition.expression_type {
+            ExpressionType::And => {
+                return self.index_lookup(l_operand).or_else(|| self.index_lookup(r_operand));
+            },
+            ExpressionType::Equal => {},
+            _ => { return None; }
+        }
+        let (name, value) = match (&l_operand.expression_type, &r_operand.expression_type) {
+            (ExpressionType::Identifier(_), ExpressionType::Identifier(_)) => { return None; },
+            (ExpressionType::Identifier(name), value) | (value, ExpressionType::Identifier(name))
+                if value.is_literal() => (name, value),
+            _ => { return None; }
+        };
+        let column = self.get_column(name)?;
+        column.index.as_ref()?;
+        Some((column, FieldValue::from_expression_type(value.clone()).index_key()))
+    }
+
+    // How the rows satisfying `condition` would be found,
+    // as shown by `explain`.
+    pub fn plan(&self, condition: Option<&Expression>) -> String {
+        match condition.and_then(|condition| self.index_lookup(condition)) {
+            Some((column, _)) => format!("index lookup on `{}` in `{}`", column.name, self.name),
+            None => format!("full scan of `{}`", self.name)
+        }
+    }
+
+    // Yields the rows that satisfy `condition` lazily, so
+    // each row is only built (and checked) once it's asked
+    // for. If the condition can't be evaluated for a row,
+    // the error is yielded in its place.
+    pub fn iter_rows<'a>(&'a self, condition: Option<&'a Expression>) -> impl Iterator<Item = Result<Row, CoilError>> + 'a {
+        self.iter_numbered_rows(condition, None).map(|result| result.map(|(_, row)| row))
+    }
+
+    // `iter_rows`, but with each row's index. If more than
+    // `max_scanned` rows would have to be looked at (whether
+    // they match or not), a `CoilError::ScanLimitExceeded`
+    // is yielded in place of the rest.
+    fn iter_numbered_rows<'a>(&'a self, condition: Option<&'a Expression>, max_scanned: Option<usize>) -> impl Iterator<Item = Result<(usize, Row), CoilError>> + 'a {
+        let indices: Box<dyn Iterator<Item = usize>> = match condition.and_then(|condition| self.indexed_rows(condition)) {
+            Some(indices) => Box::new(indices.into_iter()),
+            None => Box::new(0..self.row_count())
+        };
+        let max_scanned = max_scanned.unwrap_or(usize::MAX);
+        indices.take(max_scanned.saturating_add(1)).enumerate().filter_map(move |(scanned, i)| {
+            if scanned == max_scanned {
+                return Some(Err(CoilError::ScanLimitExceeded(max_scanned)));
+            }
+            let row = Row::from_columns(&self.columns, i);
+            match condition.map(|condition| row.check_condition(condition)) {
+                None | Some(Ok(true)) => Some(Ok((i, row))),
+                Some(Ok(false)) => None,
+                Some(Err(error)) => Some(Err(error))
+            }
+        })
+    }
+
+    // Sets columns to new values in the rows that satisfy
+    // `condition` (or every row, without one), returning
+    // how many rows were changed. The values are worked
+    // out from each row as it was before the update, so
+    // `a = b, b = a` swaps them. Every value is checked
+    // before any are set, so an error leaves the table
+    // as it was.
+    pub fn update_rows(&mut self, assignments: &[(String, Box<Expression>)], condition: Option<&Expression>) -> Result<usize, CoilError> {
+        let mut columns: Vec<usize> = Vec::new();
+        for (name, _) in assignments {
+            columns.push(self.columns.iter().position(|column| column.name == *name)
+                .ok_or_else(|| CoilError::ColumnDoesntExist(name.clone()))?);
+        }
+        // In ascending order, whether they
+        // came from an index or not.
+        let rows: Vec<(usize, Row)> = self.iter_numbered_rows(condition, None)
+            .collect::<Result<_, _>>()?;
+        let numbers: Vec<usize> = rows.iter().map(|(i, _)| *i).collect();
+
+        // The new values for each assignment, row by row.
+        let mut updates: Vec<Vec<FieldValue>> = Vec::new();
+        for (&i, (_, expression)) in columns.iter().zip(assignments) {
+            let column = &self.columns[i];
+            let mut values: Vec<FieldValue> = Vec::new();
+            for (_, row) in &rows {
+                let value = row.evaluate(expression)?;
+                column.check_value(&value)?;
+                // A key value can only go in one row, and only if
+                // none of the rows that aren't changing have it.
+                if (column.is_primary || column.is_unique) && value != FieldValue::None {
+                    let clashes = values.contains(&value) || column.rows.iter().enumerate()
+                        .any(|(row, other)| *other == value && numbers.binary_search(&row).is_err());
+                    if clashes {
+                        return Err(column.duplicate_error());
+                    }
+                }
+                values.push(value);
+            }
+            updates.push(values);
+        }
+
+        for (&i, values) in columns.iter().zip(updates) {
+            for (&row, value) in numbers.iter().zip(values) {
+                self.columns[i].set(row, value)?;
+            }
+        }
+        Ok(rows.len())
+    }
+
+    // Removes every row, returning how many there were.
+    // Unlike a delete without a condition, no rows are
+    // looked at, and auto columns start counting from 1
+    // again.
+    pub fn truncate(&mut self) -> usize {
+        let removed = self.row_count();
+        for column in &mut self.columns {
+            column.rows.clear();
+            column.last_auto = 0;
+            if column.is_indexed {
+                column.build_index();
+            }
+        }
+        removed
+    }
+
+    // Removes the rows that satisfy `condition` (or every
+    // row, without one), returning how many were removed.
+    // The condition is checked against every row before
+    // any are removed, so an error leaves the table as
+    // it was.
+    pub fn delete_rows(&mut self, condition: Option<&Expression>) -> Result<usize, CoilError> {
+        let mut doomed = vec![false; self.row_count()];
+        for result in self.iter_numbered_rows(condition, None) {
+            doomed[result?.0] = true;
+        }
+        let removed = doomed.iter().filter(|doomed| **doomed).count();
+        if removed == 0 {
+            return Ok(0);
+        }
+
+        for column in &mut self.columns {
+            let mut i = 0;
+            column.rows.retain(|_| {
+                i += 1;
+                !doomed[i - 1]
+            });
+            if column.index.is_some() {
+                column.build_index();
+            }
+        }
+        Ok(removed)
+    }
+
+    // Whether any row satisfies `condition`. Stops
+    // looking as soon as one does, or once more than
+    // `max_scanned` rows have been looked at.
+    pub fn any(&self, condition: &Expression, max_scanned: Option<usize>) -> Result<bool, CoilError> {
+        self.scan_rows(Some(condition), Some(1), max_scanned).map(|rows| !rows.is_empty())
+    }
+
+    // Every row, in order. A table without
+    // any columns doesn't have any rows.
+    pub fn all_rows(&self) -> Vec<Row> {
+        (0..self.row_count()).map(|i| Row::from_columns(&self.columns, i)).collect()
+    }
+
+    // Stops collecting as soon as `limit` matching rows
+    // have been found, so the rest of the table isn't
+    // even looked at.
+    pub fn get_rows(&self, condition: Option<Expression>, limit: Option<usize>) -> Result<Vec<Row>, CoilError> {
+        self.scan_rows(condition.as_ref(), limit, None)
+    }
+
+    // `get_rows`, but giving up with a `CoilError::ScanLimitExceeded`
+    // if more than `max_scanned` rows would have to be looked
+    // at to find them, however few of them match.
+    pub fn scan_rows(&self, condition: Option<&Expression>, limit: Option<usize>, max_scanned: Option<usize>) -> Result<Vec<Row>, CoilError> {
+        self.iter_numbered_rows(condition, max_scanned)
+            .map(|result| result.map(|(_, row)| row))
+            .take(limit.unwrap_or(usize::MAX))
+            .collect()
     }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+pub struct Row {
+    // In the order the row's columns were added, which
+    // is the table's column order (or the projection's).
+    columns: IndexMap<String, FieldValue>
+}
+
+impl Row {
+    pub fn from_columns(columns: &Vec<Column>, index: usize) -> Self {
+        let mut row = Row{columns: IndexMap::new()};
+        for column in columns {
+            row.columns.insert(column.name.clone(), column.rows[index].clone());
+        }
+        row
+    }
+
+    // Builds a new row holding only the given fields.
+    pub fn project(&self, fields: &[String]) -> Self {
+        let mut row = Row{columns: IndexMap::new()};
+        for field in fields {
+            if let Some(value) = self.get(field) {
+                row.columns.insert(field.clone(), value.clone());
+            }
+        }
+        row
+    }
+
+    // `project`, but with each field renamed, given
+    // as (field, new name) pairs.
+    pub fn project_as(&self, fields: &[(String, String)]) -> Self {
+        let mut row = Row{columns: IndexMap::new()};
+        for (field, name) in fields {
+            if let Some(value) = self.get(field) {
+                row.columns.insert(name.clone(), value.clone());
+            }
+        }
+        row
+    }
+
+    pub fn get(&self, field: &str) -> Option<&FieldValue> {
+        self.columns.get(field)
+    }
+
+    // The row's fields, in column order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FieldValue)> {
+        self.columns.iter().map(|(name, value)| (name.as_str(), value))
+    }
+
+    // These return `None` if the field doesn't exist,
+    // or if it holds a different kind of value.
+    pub fn get_int(&self, field: &str) -> Option<i64> {
+        match self.get(field)? {
+            FieldValue::Integer(number) => Some(*number),
+            _ => None
+        }
+    }
+
+    // Integers are converted, since number
+    // columns can hold either.
+    pub fn get_float(&self, field: &str) -> Option<f64> {
+        self.get(field)?.as_float()
+    }
+
+    pub fn get_text(&self, field: &str) -> Option<&str> {
+        match self.get(field)? {
+            FieldValue::Text(text) => Some(text.as_str()),
+            _ => None
+        }
+    }
+
+    pub fn get_bool(&self, field: &str) -> Option<bool> {
+        match self.get(field)? {
+            FieldValue::Boolean(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    // Resolves an expression node into the value it
+    // stands for. Identifiers are looked up in the row,
+    // literals are converted into FieldValues, and
+    // arithmetic subtrees are folded into a single value.
+    pub fn evaluate(&self, expression: &Expression) -> Result<FieldValue, CoilError> {
+        match &expression.expression_type {
+            ExpressionType::Identifier(identifier) => {
+                self.get(identifier.as_str()).cloned()
+                    .ok_or_else(|| CoilError::ColumnDoesntExist(identifier.clone()))
+            },
+            ExpressionType::Placeholder(index) => Err(CoilError::UnboundPlaceholder(*index)),
+            // Only a group's row has its aggregates in it.
+            ExpressionType::Aggregate(aggregate) => {
+                let name = aggregate.name();
+                self.get(name.as_str()).cloned().ok_or(CoilError::ColumnDoesntExist(name))
+            },
+            ExpressionType::Negate | ExpressionType::Positive => {
+                let operand = expression.l_operand.as_ref().ok_or(CoilError::IncompatibleTypes)?;
+                let value = self.evaluate(operand)?;
+                if expression.expression_type == ExpressionType::Positive {
+                    return match value {
+                        FieldValue::None | FieldValue::Integer(_) | FieldValue::Float(_) => Ok(value),
+                        _ => Err(CoilError::IncompatibleTypes)
+                    };
+                }
+                // Negating is just subtracting from zero.
+                FieldValue::arithmetic(&ExpressionType::Subtract, FieldValue::Integer(0), value)
+            },
+            ExpressionType::Add | ExpressionType::Subtract
+            | ExpressionType::Multiply | ExpressionType::Divide
+            | ExpressionType::Modulus | ExpressionType::Power => {
+                let (l_operand, r_operand) = expression.operands().ok_or(CoilError::IncompatibleTypes)?;
+                FieldValue::arithmetic(&expression.expression_type,
+                                       self.evaluate(l_operand)?,
+                                       self.evaluate(r_operand)?)
+            },
+            // Conditions evaluate to booleans, so they
+            // can be compared like any other value.
+            expression_type if expression_type.is_predicate() => {
+                Ok(FieldValue::Boolean(self.check_condition(expression)?))
+            },
+            expression_type if expression_type.is_literal() => {
+                Ok(FieldValue::from_expression_type(expression_type.clone()))
+            },
+            _ => Err(CoilError::IncompatibleTypes)
+        }
+    }
+
+    // Walks the condition tree recursively. Logical
+    // operators combine the results of their operands
+    // (which can be arbitrarily nested), while comparisons
+    // evaluate each side down to a value first.
+    //
+    // There's no implicit truthiness: the operands of
+    // `and`, `or` and `xor` have to be conditions
+    // themselves (or boolean values), so something like
+    // `where ID and 1` is a `CoilError::IncompatibleTypes`.
+    // `and` and `or` short-circuit, so their right operand
+    // is only checked when the left one doesn't decide
+    // the result.
+    pub fn check_condition(&self, condition: &Expression) -> Result<bool, CoilError> {
+        if !condition.expression_type.is_predicate() {
+            return match self.evaluate(condition)? {
+                FieldValue::Boolean(value) => Ok(value),
+                _ => Err(CoilError::NonBooleanCondition)
+            };
+        }
+        // Like every unary operator, `not` keeps its
+        // operand in `l_operand`.
+        if condition.expression_type == ExpressionType::Not {
+            let operand = condition.l_operand.as_ref().ok_or(CoilError::IncompatibleTypes)?;
+            return Ok(!self.check_condition(operand)?);
+        }
+        // `is none` works the same way whatever the
+        // column's type is, and unlike `= none` it
+        // makes checking for nulls explicit.
+        if let ExpressionType::IsNone | ExpressionType::IsNotNone = condition.expression_type {
+            let operand = condition.l_operand.as_ref().ok_or(CoilError::IncompatibleTypes)?;
+            let is_none = self.evaluate(operand)? == FieldValue::None;
+            return Ok(is_none == (condition.expression_type == ExpressionType::IsNone));
+        }
+        if let ExpressionType::In(candidates) = &condition.expression_type {
+            let operand = condition.l_operand.as_ref().ok_or(CoilError::IncompatibleTypes)?;
+            let value = self.evaluate(operand)?;
+            let candidates: Vec<FieldValue> = candidates.iter()
+                .map(|candidate| self.evaluate(candidate))
+                .collect::<Result<_, _>>()?;
+            if candidates.iter().any(|candidate| !value.is_comparable(candidate)) {
+                return Err(CoilError::IncompatibleTypes);
+            }
+            return Ok(candidates.contains(&value));
+        }
+        let (l_operand, r_operand) = condition.operands().ok_or(CoilError::IncompatibleTypes)?;
+
+        match condition.expression_type {
+            ExpressionType::And => {
+                return Ok(self.check_condition(l_operand)? && self.check_condition(r_operand)?);
+            },
+            ExpressionType::Or => {
+                return Ok(self.check_condition(l_operand)? || self.check_condition(r_operand)?);
+            },
+            ExpressionType::Xor => {
+                return Ok(self.check_condition(l_operand)? != self.check_condition(r_operand)?);
+            },
+            _ => {}
+        }
+
+        let l_value = self.evaluate(l_operand)?;
+        let r_value = self.evaluate(r_operand)?;
+        if condition.expression_type == ExpressionType::Like {
+            // Only text can be matched against a pattern, so
+            // anything else doesn't fit, like putting it in a
+            // text column wouldn't.
+            for (operand, value) in [(l_operand, &l_value), (r_operand, &r_value)] {
+                if let FieldValue::Text(_) | FieldValue::None = value {
+                    continue;
+                }
+                let column = match &operand.expression_type {
+                    ExpressionType::Identifier(identifier) => identifier.clone(),
+                    _ => value.to_literal()
+                };
+                return Err(CoilError::MismatchedTypes{column: column,
+                                                      expected: FieldType::Text,
+                                                      got: value.clone()});
+            }
+            return l_value.like(&r_value);
+        }
+        let ordering = match (&l_value, &r_value) {
+            (FieldValue::Text(l), FieldValue::Text(r))
+                if condition.expression_type == ExpressionType::EqualIgnoringCase => {
+                l.to_lowercase().partial_cmp(&r.to_lowercase())
+            },
+            _ => l_value.compare(&r_value)?
+        };
+
+        match condition.expression_type {
+            ExpressionType::Equal
+            | ExpressionType::EqualIgnoringCase => Ok(ordering == Some(Ordering::Equal)),
+            ExpressionType::NotEqual => Ok(ordering != Some(Ordering::Equal)),
+            ExpressionType::LessThan => Ok(ordering == Some(Ordering::Less)),
+            ExpressionType::LessThanOrEqual => Ok(matches!(ordering, Some(Ordering::Less | Ordering::Equal))),
+            ExpressionType::GreaterThan => Ok(ordering == Some(Ordering::Greater)),
+            ExpressionType::GreaterThanOrEqual => Ok(matches!(ordering, Some(Ordering::Greater | Ordering::Equal))),
+            _ => Err(CoilError::NonBooleanCondition)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Column {
     pub name: String,
     pub rows: Vec<FieldValue>,
-    pub field_type: FieldType
+    pub field_type: FieldType,
+    // Primary key columns can't hold duplicates or `none`s.
+    #[serde(default)]
+    pub is_primary: bool,
+    // Unique columns can't hold duplicates, but
+    // any number of rows can leave them `none`.
+    #[serde(default)]
+    pub is_unique: bool,
+    // Columns are nullable unless they're declared `notnull`.
+    #[serde(default)]
+    pub is_not_null: bool,
+    // Used when an insert leaves the column out.
+    #[serde(default)]
+    pub default: Option<FieldValue>,
+    // Auto columns number their rows, counting up
+    // from 1 when an insert doesn't give them a value.
+    #[serde(default)]
+    pub is_auto: bool,
+    // The highest value the auto column has seen.
+    #[serde(default)]
+    pub last_auto: i64,
+    // Whether the column has an index. This is what gets
+    // saved, since the index can be rebuilt from the rows.
+    #[serde(default)]
+    pub is_indexed: bool,
+    // Maps each value in the column to the rows
+    // holding it, if the column is indexed.
+    #[serde(skip)]
+    pub index: Option<HashMap<IndexKey, Vec<usize>>>
 }
 
 impl Column {
     pub fn new(name: String, field_type: FieldType) -> Self {
-        Column{name: name, rows: Vec::new(), field_type: field_type}
+        Column{name: name, rows: Vec::new(), field_type: field_type, is_primary: false, is_unique: false, is_not_null: false, default: None,
+               is_auto: false, last_auto: 0, is_indexed: false, index: None}
+    }
+
+    // The column's modifiers as they'd be written
+    // in a create query, e.g. "primary auto".
+    pub fn modifiers(&self) -> String {
+        let mut modifiers: Vec<String> = Vec::new();
+        if self.is_primary {
+            modifiers.push(String::from("primary"));
+        }
+        if self.is_unique {
+            modifiers.push(String::from("unique"));
+        }
+        if self.is_not_null {
+            modifiers.push(String::from("notnull"));
+        }
+        if let Some(default) = &self.default {
+            modifiers.push(format!("default {}", default.to_literal()));
+        }
+        if self.is_auto {
+            modifiers.push(String::from("auto"));
+        }
+        modifiers.join(" ")
+    }
+
+    // The column as it'd be written in a create
+    // query, e.g. "ID: number primary auto".
+    pub fn to_ddl(&self) -> String {
+        let modifiers = self.modifiers();
+        if modifiers.is_empty() {
+            format!("{}: {}", quote_name(&self.name), self.field_type.name())
+        }
+        else {
+            format!("{}: {} {}", quote_name(&self.name), self.field_type.name(), modifiers)
+        }
+    }
+
+    // Checks that the column's modifiers make sense
+    // for its type.
+    pub fn check_definition(&self) -> Result<(), CoilError> {
+        if let Some(default) = &self.default {
+            if !self.field_type.check_field_value_type(default) {
+                return Err(self.mismatch(default));
+            }
+        }
+        if self.is_auto && self.field_type != FieldType::Number {
+            return Err(CoilError::IncompatibleTypes);
+        }
+        Ok(())
+    }
+
+    // Checks whether `value` could be added to the
+    // column without breaking any of its constraints
+    // (its type included).
+    pub fn check_constraints(&self, value: &FieldValue) -> Result<(), CoilError> {
+        self.check_value(value)?;
+        if (self.is_primary || self.is_unique) && *value != FieldValue::None && self.rows.contains(value) {
+            return Err(self.duplicate_error());
+        }
+        Ok(())
+    }
+
+    // The constraints that don't depend on the
+    // column's other values: its type, and
+    // whether it can hold `none`.
+    fn check_value(&self, value: &FieldValue) -> Result<(), CoilError> {
+        if !self.field_type.check_field_value_type(value) {
+            return Err(self.mismatch(value));
+        }
+        if (self.is_primary || self.is_not_null) && *value == FieldValue::None {
+            return Err(CoilError::NullConstraintViolation(self.name.clone()));
+        }
+        Ok(())
+    }
+
+    // The error for a value that's already in a key column.
+    fn duplicate_error(&self) -> CoilError {
+        if self.is_primary {
+            CoilError::DuplicateKey
+        }
+        else {
+            CoilError::UniqueViolation(self.name.clone())
+        }
     }
 
     pub fn push(&mut self, value: FieldValue) -> Result<(), CoilError> {
         if self.field_type.check_field_value_type(&value) {
+            if let Some(index) = &mut self.index {
+                index.entry(value.index_key()).or_default().push(self.rows.len());
+            }
+            self.note_auto_value(&value);
             self.rows.push(value);
             return Ok(());
         }
-        Err(CoilError::MismatchedTypes)
+        Err(self.mismatch(&value))
+    }
+
+    // Overwrites the value in one row. Like `push`, this
+    // only checks the value's type, not the constraints.
+    pub fn set(&mut self, row: usize, value: FieldValue) -> Result<(), CoilError> {
+        if !self.field_type.check_field_value_type(&value) {
+            return Err(self.mismatch(&value));
+        }
+        if let Some(index) = &mut self.index {
+            if let Some(rows) = index.get_mut(&self.rows[row].index_key()) {
+                rows.retain(|i| *i != row);
+            }
+            let rows = index.entry(value.index_key()).or_default();
+            rows.push(row);
+            rows.sort_unstable();
+        }
+        self.note_auto_value(&value);
+        self.rows[row] = value;
+        Ok(())
+    }
+
+    // Explicit values in an auto column move its counter
+    // along too, so generated ones never collide with them.
+    fn note_auto_value(&mut self, value: &FieldValue) {
+        if !self.is_auto {
+            return;
+        }
+        if let Some(number) = value.as_float() {
+            self.last_auto = self.last_auto.max(number.floor() as i64);
+        }
+    }
+
+    fn mismatch(&self, value: &FieldValue) -> CoilError {
+        CoilError::MismatchedTypes{column: self.name.clone(),
+                                   expected: self.field_type.clone(),
+                                   got: value.clone()}
+    }
+
+    // (Re)builds the column's index from scratch.
+    pub fn build_index(&mut self) {
+        let mut index: HashMap<IndexKey, Vec<usize>> = HashMap::new();
+        for (i, value) in self.rows.iter().enumerate() {
+            index.entry(value.index_key()).or_default().push(i);
+        }
+        self.index = Some(index);
+        self.is_indexed = true;
+    }
+
+    // The column's values as integers, without copying the
+    // column. `none`s, and values that aren't integers,
+    // come out as `None`.
+    pub fn ints(&self) -> impl Iterator<Item = Option<i64>> + '_ {
+        self.rows.iter().map(|value| match value {
+            FieldValue::Integer(number) => Some(*number),
+            _ => None
+        })
+    }
+
+    // `ints`, but for floats. Integers are
+    // turned into floats, too.
+    pub fn floats(&self) -> impl Iterator<Item = Option<f64>> + '_ {
+        self.rows.iter().map(FieldValue::as_float)
+    }
+
+    // `ints`, but for text.
+    pub fn texts(&self) -> impl Iterator<Item = Option<&str>> + '_ {
+        self.rows.iter().map(|value| match value {
+            FieldValue::Text(text) => Some(text.as_str()),
+            _ => None
+        })
+    }
+
+    // `ints`, but for booleans.
+    pub fn booleans(&self) -> impl Iterator<Item = Option<bool>> + '_ {
+        self.rows.iter().map(|value| match value {
+            FieldValue::Boolean(value) => Some(*value),
+            _ => None
+        })
     }
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum FieldType {
     Text,
-    Number
+    Number,
+    Boolean
 }
 
 impl FieldType {
@@ -350,18 +2322,177 @@ impl FieldType {
         match *field_value {
             FieldValue::None => true,
             FieldValue::Text(_) => self == &FieldType::Text,
+            FieldValue::Boolean(_) => self == &FieldType::Boolean,
             FieldValue::Integer(_)
             | FieldValue::Float(_) => self == &FieldType::Number
         }
     }
+
+    // The type's keyword in queries.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldType::Text => "text",
+            FieldType::Number => "number",
+            FieldType::Boolean => "bool"
+        }
+    }
+
+    // Reads a value of this type from text, like a CSV
+    // field. Empty text is `none`.
+    pub fn parse_field_value(&self, text: &str) -> Option<FieldValue> {
+        if text.is_empty() {
+            return Some(FieldValue::None);
+        }
+        match self {
+            FieldType::Text => Some(FieldValue::Text(String::from(text))),
+            FieldType::Number => {
+                if let Ok(number) = text.parse::<i64>() {
+                    return Some(FieldValue::Integer(number));
+                }
+                // Like in queries, integers too big for an
+                // i64 aren't turned into floats.
+                let digits = text.strip_prefix('-').unwrap_or(text);
+                if digits.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                text.parse::<f64>().ok().map(FieldValue::Float)
+            },
+            FieldType::Boolean => text.parse::<bool>().ok().map(FieldValue::Boolean)
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd, Deserialize, Serialize)]
+// FieldValues can't be hashed (because of their floats),
+// so indexes use these as their keys instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IndexKey {
+    None,
+    Text(String),
+    Integer(i64),
+    // The float's bits.
+    Float(u64),
+    Boolean(bool)
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum FieldValue {
     None,
     Text(String),
     Integer(i64),
-    Float(f64)
+    Float(f64),
+    Boolean(bool)
+}
+
+impl PartialEq for FieldValue {
+    fn eq(&self, other: &FieldValue) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+// Integers and floats are compared by their values, so
+// `5 = 5.0`. Otherwise, values of different kinds are
+// ordered `none`, text, numbers, then booleans.
+impl PartialOrd for FieldValue {
+    fn partial_cmp(&self, other: &FieldValue) -> Option<Ordering> {
+        let rank = |value: &FieldValue| {
+            match value {
+                FieldValue::None => 0,
+                FieldValue::Text(_) => 1,
+                FieldValue::Integer(_) | FieldValue::Float(_) => 2,
+                FieldValue::Boolean(_) => 3
+            }
+        };
+        match (self, other) {
+            (FieldValue::Text(l), FieldValue::Text(r)) => l.partial_cmp(r),
+            (FieldValue::Integer(l), FieldValue::Integer(r)) => l.partial_cmp(r),
+            (FieldValue::Boolean(l), FieldValue::Boolean(r)) => l.partial_cmp(r),
+            _ if rank(self) == 2 && rank(other) == 2 => {
+                self.as_float().unwrap().partial_cmp(&other.as_float().unwrap())
+            },
+            _ => rank(self).partial_cmp(&rank(other))
+        }
+    }
+}
+
+impl From<i64> for FieldValue {
+    fn from(number: i64) -> Self {
+        FieldValue::Integer(number)
+    }
+}
+
+impl From<f64> for FieldValue {
+    fn from(number: f64) -> Self {
+        FieldValue::Float(number)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(text: &str) -> Self {
+        FieldValue::Text(String::from(text))
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(text: String) -> Self {
+        FieldValue::Text(text)
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Boolean(value)
+    }
+}
+
+// `None` becomes `FieldValue::None`.
+impl<T: Into<FieldValue>> From<Option<T>> for FieldValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(FieldValue::None, Into::into)
+    }
+}
+
+// Going the other way works like `Row`'s typed
+// accessors, so the value has to be the right
+// kind, except that integers can become floats.
+impl TryFrom<FieldValue> for i64 {
+    type Error = CoilError;
+
+    fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+        match value {
+            FieldValue::Integer(number) => Ok(number),
+            _ => Err(CoilError::IncompatibleTypes)
+        }
+    }
+}
+
+impl TryFrom<FieldValue> for f64 {
+    type Error = CoilError;
+
+    fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+        value.as_float().ok_or(CoilError::IncompatibleTypes)
+    }
+}
+
+impl TryFrom<FieldValue> for String {
+    type Error = CoilError;
+
+    fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+        match value {
+            FieldValue::Text(text) => Ok(text),
+            _ => Err(CoilError::IncompatibleTypes)
+        }
+    }
+}
+
+impl TryFrom<FieldValue> for bool {
+    type Error = CoilError;
+
+    fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+        match value {
+            FieldValue::Boolean(value) => Ok(value),
+            _ => Err(CoilError::IncompatibleTypes)
+        }
+    }
 }
 
 impl FieldValue {
@@ -371,18 +2502,250 @@ impl FieldValue {
             ExpressionType::String(string) => FieldValue::Text(string),
             ExpressionType::Integer(number) => FieldValue::Integer(number),
             ExpressionType::Float(number) => FieldValue::Float(number),
+            ExpressionType::Boolean(value) => FieldValue::Boolean(value),
             // Hmm... this constructor could
             // return an Option<Self> maybe...
             _ => FieldValue::None
         }
     }
 
-    pub fn to_string(&self) -> String {
+    pub fn to_expression_type(&self) -> ExpressionType {
+        match self {
+            FieldValue::None => ExpressionType::None,
+            FieldValue::Text(string) => ExpressionType::String(string.clone()),
+            FieldValue::Integer(number) => ExpressionType::Integer(*number),
+            FieldValue::Float(number) => ExpressionType::Float(*number),
+            FieldValue::Boolean(value) => ExpressionType::Boolean(*value)
+        }
+    }
+
+    // Folds a binary arithmetic operation into a single
+    // value. Two integers stay integers (so division
+    // truncates), but if either side is a float, both
+    // are promoted to floats. Arithmetic involving a
+    // `none` is just `none`, and text can't take part.
+    //
+    // `%` takes the sign of its left side, like Rust's,
+    // and works on floats too. An integer raised to a
+    // negative power is a float, since it's a fraction.
+    //
+    // Floats that come out infinite or NaN are errors,
+    // the same as integers that overflow, since they
+    // can't be saved.
+    pub fn arithmetic(operator: &ExpressionType, l_value: FieldValue, r_value: FieldValue) -> Result<FieldValue, CoilError> {
+        match (l_value, r_value) {
+            (FieldValue::None, _) | (_, FieldValue::None) => Ok(FieldValue::None),
+            (FieldValue::Integer(l), FieldValue::Integer(r)) => {
+                let value = match operator {
+                    ExpressionType::Add => l.checked_add(r),
+                    ExpressionType::Subtract => l.checked_sub(r),
+                    ExpressionType::Multiply => l.checked_mul(r),
+                    ExpressionType::Divide
+                    | ExpressionType::Modulus if r == 0 => {
+                        return Err(CoilError::DivisionByZero);
+                    },
+                    ExpressionType::Divide => l.checked_div(r),
+                    ExpressionType::Modulus => l.checked_rem(r),
+                    ExpressionType::Power if r < 0 => {
+                        return FieldValue::arithmetic(operator, FieldValue::Float(l as f64), FieldValue::Float(r as f64));
+                    },
+                    // An exponent too big for a u32 overflows
+                    // anyway, unless the base is 0, 1 or -1.
+                    ExpressionType::Power => u32::try_from(r).ok().and_then(|r| l.checked_pow(r)),
+                    _ => { return Err(CoilError::IncompatibleTypes); }
+                };
+                value.map(FieldValue::Integer).ok_or(CoilError::ArithmeticOverflow)
+            },
+            (l_value, r_value) => {
+                let (Some(l), Some(r)) = (l_value.as_float(), r_value.as_float()) else {
+                    return Err(CoilError::IncompatibleTypes);
+                };
+                let value = match operator {
+                    ExpressionType::Add => l + r,
+                    ExpressionType::Subtract => l - r,
+                    ExpressionType::Multiply => l * r,
+                    ExpressionType::Divide
+                    | ExpressionType::Modulus if r == 0.0 => {
+                        return Err(CoilError::DivisionByZero);
+                    },
+                    ExpressionType::Divide => l / r,
+                    ExpressionType::Modulus => l % r,
+                    // `0 ^ -1` is `1 / 0`.
+                    ExpressionType::Power if l == 0.0 && r < 0.0 => {
+                        return Err(CoilError::DivisionByZero);
+                    },
+                    ExpressionType::Power => l.powf(r),
+                    _ => { return Err(CoilError::IncompatibleTypes); }
+                };
+                if value.is_nan() {
+                    Err(CoilError::NotANumber)
+                }
+                else if value.is_infinite() {
+                    Err(CoilError::ArithmeticOverflow)
+                }
+                else {
+                    Ok(FieldValue::Float(value))
+                }
+            }
+        }
+    }
+
+    // Whether two values can be meaningfully compared:
+    // numbers with numbers, text with text and booleans
+    // with booleans. `none` can be compared with anything.
+    pub fn is_comparable(&self, other: &FieldValue) -> bool {
+        match (self, other) {
+            (FieldValue::None, _) | (_, FieldValue::None) => true,
+            (FieldValue::Text(_), FieldValue::Text(_)) => true,
+            (FieldValue::Boolean(_), FieldValue::Boolean(_)) => true,
+            _ => self.as_float().is_some() && other.as_float().is_some()
+        }
+    }
+
+    // Compares two values that `is_comparable` allows to
+    // be compared, and errors for any others, so that
+    // something like `"5" = 5` can't quietly be false.
+    // Comparisons involving NaN have no ordering.
+    pub fn compare(&self, other: &FieldValue) -> Result<Option<Ordering>, CoilError> {
+        if !self.is_comparable(other) {
+            return Err(CoilError::IncompatibleTypes);
+        }
+        Ok(self.partial_cmp(other))
+    }
+
+    // Matches text against a `like` pattern, where `%`
+    // stands for any run of characters, `_` for exactly
+    // one, and a backslash makes the next character
+    // literal. `none` never matches.
+    pub fn like(&self, pattern: &FieldValue) -> Result<bool, CoilError> {
+        enum Part {
+            Any,
+            One,
+            Char(char)
+        }
+
+        let (text, pattern) = match (self, pattern) {
+            (FieldValue::Text(text), FieldValue::Text(pattern)) => (text, pattern),
+            (FieldValue::None, _) | (_, FieldValue::None) => { return Ok(false); },
+            _ => { return Err(CoilError::IncompatibleTypes); }
+        };
+
+        let mut parts: Vec<Part> = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            parts.push(match c {
+                '%' => Part::Any,
+                '_' => Part::One,
+                '\\' => Part::Char(chars.next().unwrap_or('\\')),
+                _ => Part::Char(c)
+            });
+        }
+
+        // Whenever a char doesn't match, we go back to the
+        // last `%` and let it swallow one more char.
+        let text: Vec<char> = text.chars().collect();
+        let (mut t, mut p) = (0, 0);
+        let mut last_any: Option<(usize, usize)> = None;
+        while t < text.len() {
+            match parts.get(p) {
+                Some(Part::Any) => {
+                    last_any = Some((p, t));
+                    p += 1;
+                    continue;
+                },
+                Some(Part::One) => {
+                    t += 1;
+                    p += 1;
+                    continue;
+                },
+                Some(Part::Char(c)) if *c == text[t] => {
+                    t += 1;
+                    p += 1;
+                    continue;
+                },
+                _ => {}
+            }
+            let Some((any_p, any_t)) = last_any else {
+                return Ok(false);
+            };
+            last_any = Some((any_p, any_t + 1));
+            p = any_p + 1;
+            t = any_t + 1;
+        }
+
+        Ok(parts[p..].iter().all(|part| matches!(part, Part::Any)))
+    }
+
+    // The ordering used by `order by`. It's the same as
+    // the PartialOrd one, except that `none`s always go
+    // after everything else (so they come last when
+    // sorting ascending, and first when descending).
+    pub fn sort_order(&self, other: &FieldValue) -> Ordering {
+        match (self, other) {
+            (FieldValue::None, FieldValue::None) => Ordering::Equal,
+            (FieldValue::None, _) => Ordering::Greater,
+            (_, FieldValue::None) => Ordering::Less,
+            _ => self.partial_cmp(other).unwrap_or(Ordering::Equal)
+        }
+    }
+
+    // Values that are equal always have the same key.
+    pub fn index_key(&self) -> IndexKey {
+        match self {
+            FieldValue::None => IndexKey::None,
+            FieldValue::Text(text) => IndexKey::Text(text.clone()),
+            FieldValue::Integer(number) => IndexKey::Integer(*number),
+            // Whole floats equal the integers they're the
+            // same as, and -0.0 == 0.0 (but their bits differ).
+            FieldValue::Float(number) if number.fract() == 0.0
+                && *number >= i64::MIN as f64 && *number < i64::MAX as f64 => {
+                IndexKey::Integer(*number as i64)
+            },
+            FieldValue::Float(number) => IndexKey::Float(number.to_bits()),
+            FieldValue::Boolean(value) => IndexKey::Boolean(*value)
+        }
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        match *self {
+            FieldValue::Integer(number) => Some(number as f64),
+            FieldValue::Float(number) => Some(number),
+            _ => None
+        }
+    }
+
+    // Like `to_string`, but with floats shown with a fixed
+    // number of decimal places, if `precision` is given.
+    pub fn format(&self, precision: Option<usize>) -> String {
+        match (self, precision) {
+            (FieldValue::Float(number), Some(precision)) => format!("{:.*}", precision, number),
+            (value, _) => value.to_string()
+        }
+    }
+
+    // The value written the way it would be in a
+    // query, so text is quoted and escaped. Floats
+    // always have a decimal point, so `1.0` doesn't
+    // come back as an integer.
+    pub fn to_literal(&self) -> String {
+        match self {
+            FieldValue::None => String::from("none"),
+            FieldValue::Text(text) => format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"")
+                                                          .replace('\n', "\\n").replace('\t', "\\t")),
+            FieldValue::Float(number) if number.fract() == 0.0 => format!("{}.0", number),
+            value => value.to_string()
+        }
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            FieldValue::None => String::from("None"),
-            FieldValue::Text(string) => string.to_string(),
-            FieldValue::Integer(number) => number.to_string(),
-            FieldValue::Float(number) => number.to_string()
+            FieldValue::None => write!(f, "None"),
+            FieldValue::Text(string) => write!(f, "{}", string),
+            FieldValue::Integer(number) => write!(f, "{}", number),
+            FieldValue::Float(number) => write!(f, "{}", number),
+            FieldValue::Boolean(value) => write!(f, "{}", value)
         }
     }
 }
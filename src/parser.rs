@@ -1,4 +1,4 @@
-use crate::{FieldValue, FieldType, Column};
+use crate::{FieldValue, FieldType, Column, CoilError};
 use crate::lexer::*;
 
 #[derive(Debug, PartialEq)]
@@ -7,7 +7,88 @@ pub enum Operation {
     Put,
     Update,
     Create,
-    Delete
+    Delete,
+    Alter,
+    // Removes every row from a table.
+    Truncate,
+    // Lists a table's columns.
+    Describe,
+    // Lists the database's tables.
+    Show,
+    Begin,
+    Commit,
+    Rollback,
+    // Describes how a query would run,
+    // without running it.
+    Explain
+}
+
+impl Operation {
+    // Whether the query's result has rows to show.
+    pub fn returns_rows(&self) -> bool {
+        match *self {
+            Operation::Get
+            | Operation::Describe
+            | Operation::Show => true,
+            _ => false
+        }
+    }
+}
+
+// The change an alter query makes to its table.
+#[derive(Debug)]
+pub enum Alteration {
+    AddColumn(Column),
+    DropColumn(String),
+    RenameTable(String),
+    // The column's current name, then its new one.
+    RenameColumn(String, String)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum AggregateFunction {
+    Count,
+    // `count(distinct col)`, which only counts
+    // each different value once.
+    CountDistinct,
+    Sum,
+    Average,
+    Minimum,
+    Maximum
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    // `None` stands for `*`, which only
+    // `count` accepts.
+    pub column: Option<String>
+}
+
+#[derive(Debug)]
+pub struct Join {
+    // The table being joined onto the query's table.
+    pub table: String,
+    // Which pairs of rows to keep, e.g.
+    // `orders.cust_id = customers.ID`.
+    pub condition: Box<Expression>
+}
+
+impl Aggregate {
+    // The header used for the aggregate's
+    // column, e.g. "count(*)".
+    pub fn name(&self) -> String {
+        let function = match self.function {
+            AggregateFunction::Count
+            | AggregateFunction::CountDistinct => "count",
+            AggregateFunction::Sum => "sum",
+            AggregateFunction::Average => "avg",
+            AggregateFunction::Minimum => "min",
+            AggregateFunction::Maximum => "max"
+        };
+        let distinct = if self.function == AggregateFunction::CountDistinct { "distinct " } else { "" };
+        format!("{}({}{})", function, distinct, self.column.as_deref().unwrap_or("*"))
+    }
 }
 
 // This is largely a copy of Token,
@@ -18,7 +99,15 @@ pub enum ExpressionType {
     // Unary
     Not, Negate, Positive,
     // Binary
-    Equal, NotEqual,
+    Equal, NotEqual, Like,
+    // Like `Equal`, but text is compared
+    // without regard to case.
+    EqualIgnoringCase,
+    // Tests `l_operand` against a list of values,
+    // which are literals or placeholders.
+    In(Vec<Expression>),
+    // Tests whether `l_operand` is `none`.
+    IsNone, IsNotNone,
     LessThan, LessThanOrEqual,
     GreaterThan, GreaterThanOrEqual,
     And, Or, Xor,
@@ -27,20 +116,54 @@ pub enum ExpressionType {
     Power, Modulus,
     // Literals
     Integer(i64), Float(f64), String(String),
-    None, Identifier(String)
+    Boolean(bool), None, Identifier(String),
+    // The nth `?` in the query, which is replaced
+    // by the value bound to it before it runs.
+    Placeholder(usize),
+    // An aggregate in a `having` clause, which
+    // is looked up in the group's row.
+    Aggregate(Aggregate)
 }
 
 impl ExpressionType {
+    // Whether the expression is a condition
+    // in its own right (i.e. always yields
+    // a boolean).
+    pub fn is_predicate(&self) -> bool {
+        match *self {
+            ExpressionType::Not
+            | ExpressionType::And
+            | ExpressionType::Or
+            | ExpressionType::Xor
+            | ExpressionType::Equal
+            | ExpressionType::NotEqual
+            | ExpressionType::Like
+            | ExpressionType::EqualIgnoringCase
+            | ExpressionType::In(_)
+            | ExpressionType::IsNone
+            | ExpressionType::IsNotNone
+            | ExpressionType::LessThan
+            | ExpressionType::LessThanOrEqual
+            | ExpressionType::GreaterThan
+            | ExpressionType::GreaterThanOrEqual => true,
+            _ => false
+        }
+    }
+
     pub fn is_literal(&self) -> bool {
-        self == ExpressionType::Integer
-        || self == ExpressionType::Float
-        || self == ExpressionType::String
-        || self == ExpressionType::None
-        || self == ExpressionType::Identifier
+        match *self {
+            ExpressionType::Integer(_)
+            | ExpressionType::Float(_)
+            | ExpressionType::String(_)
+            | ExpressionType::Boolean(_)
+            | ExpressionType::None
+            | ExpressionType::Identifier(_) => true,
+            _ => false
+        }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct Expression {
     // Literal expressions only use `expression_type`.
     pub expression_type: ExpressionType,
@@ -49,38 +172,296 @@ pub struct Expression {
     pub r_operand: Option<Box<Expression>>
 }
 
+// Shorthands for building conditions without going
+// through the lexer and parser, like
+// `Expression::and(Expression::eq("Name", "jim"), Expression::gt("ID", 1))`.
+impl Expression {
+    pub fn column(name: &str) -> Box<Expression> {
+        Box::new(Expression{expression_type: ExpressionType::Identifier(String::from(name)),
+            l_operand: None, r_operand: None})
+    }
+
+    pub fn value<T: Into<FieldValue>>(value: T) -> Box<Expression> {
+        Box::new(Expression{expression_type: value.into().to_expression_type(),
+            l_operand: None, r_operand: None})
+    }
+
+    pub fn binary(expression_type: ExpressionType, l_operand: Box<Expression>, r_operand: Box<Expression>) -> Box<Expression> {
+        Box::new(Expression{expression_type: expression_type,
+            l_operand: Some(l_operand), r_operand: Some(r_operand)})
+    }
+
+    // Compares a column with a value.
+    fn compare<T: Into<FieldValue>>(expression_type: ExpressionType, column: &str, value: T) -> Box<Expression> {
+        Expression::binary(expression_type, Expression::column(column), Expression::value(value))
+    }
+
+    pub fn eq<T: Into<FieldValue>>(column: &str, value: T) -> Box<Expression> {
+        Expression::compare(ExpressionType::Equal, column, value)
+    }
+
+    pub fn ne<T: Into<FieldValue>>(column: &str, value: T) -> Box<Expression> {
+        Expression::compare(ExpressionType::NotEqual, column, value)
+    }
+
+    pub fn lt<T: Into<FieldValue>>(column: &str, value: T) -> Box<Expression> {
+        Expression::compare(ExpressionType::LessThan, column, value)
+    }
+
+    pub fn le<T: Into<FieldValue>>(column: &str, value: T) -> Box<Expression> {
+        Expression::compare(ExpressionType::LessThanOrEqual, column, value)
+    }
+
+    pub fn gt<T: Into<FieldValue>>(column: &str, value: T) -> Box<Expression> {
+        Expression::compare(ExpressionType::GreaterThan, column, value)
+    }
+
+    pub fn ge<T: Into<FieldValue>>(column: &str, value: T) -> Box<Expression> {
+        Expression::compare(ExpressionType::GreaterThanOrEqual, column, value)
+    }
+
+    pub fn and(l_operand: Box<Expression>, r_operand: Box<Expression>) -> Box<Expression> {
+        Expression::binary(ExpressionType::And, l_operand, r_operand)
+    }
+
+    pub fn or(l_operand: Box<Expression>, r_operand: Box<Expression>) -> Box<Expression> {
+        Expression::binary(ExpressionType::Or, l_operand, r_operand)
+    }
+
+    pub fn not(operand: Box<Expression>) -> Box<Expression> {
+        Box::new(Expression{expression_type: ExpressionType::Not,
+            l_operand: Some(operand), r_operand: None})
+    }
+
+    // Both operands of a binary expression.
+    pub fn operands(&self) -> Option<(&Expression, &Expression)> {
+        match (&self.l_operand, &self.r_operand) {
+            (Some(l_operand), Some(r_operand)) => Some((l_operand, r_operand)),
+            _ => None
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Query {
     pub operation: Operation,
+    // The database being created or deleted, or
+    // the one the query's table is in, if it's
+    // qualified, like `analytics.events`.
     pub database: Option<String>,
     pub table: Option<String>,
     pub values: Option<Vec<FieldValue>>,
+    // The columns an update query sets,
+    // and the values it sets them to.
+    pub assignments: Option<Vec<(String, Box<Expression>)>>,
+    // The CSV file a put query reads its rows from.
+    pub file: Option<String>,
+    // Whether a put query overwrites rows whose
+    // keys clash with the new one's.
+    pub update_on_conflict: bool,
+    // The columns a get query returns, in order, and
+    // the names they're returned under, which are the
+    // columns' own unless they're given an alias with
+    // `as`. `None` means every column (`*`).
+    pub projection: Option<Vec<(String, String)>>,
+    // Every aggregate a get query uses, whether it's
+    // returned or only used by `having`. In the
+    // projection, they're named like "count(*)".
+    pub aggregates: Vec<Aggregate>,
+    // The columns a get query's rows are grouped by.
+    pub group_by: Option<Vec<String>>,
+    // Which groups to keep.
+    pub having: Option<Box<Expression>>,
+    // Whether repeated rows are left out.
+    pub distinct: bool,
+    // Whether a get query only checks if
+    // there are any matching rows.
+    pub exists: bool,
     pub columns: Option<Vec<Column>>,
+    pub alteration: Option<Alteration>,
+    pub join: Option<Join>,
     pub condition: Option<Box<Expression>>,
+    // The column to sort by, and whether
+    // the sort is ascending.
+    pub order_by: Option<(String, bool)>,
+    pub limit: Option<usize>,
+    // Whether a delete query removes rows
+    // (`delete from`), rather than a table.
+    pub delete_rows: bool,
+    // The query an explain query describes.
+    pub explained: Option<Box<Query>>,
+    // How many rows to skip before the limit starts.
+    pub offset: Option<usize>,
+    // Where each `?` placeholder is, in order.
+    placeholders: Vec<Placeholder>,
+    // The values bound to the placeholders so far.
+    parameters: Vec<Option<FieldValue>>
+}
+
+// Where a placeholder is in its query: somewhere in
+// one of its expressions, or at some position in
+// its values.
+#[derive(Debug, Clone, Copy)]
+enum Placeholder {
+    Expression,
+    Value(usize)
 }
 
 impl Query {
     pub fn new(operation: Operation) -> Self {
-        Query{operation: operation, database: None, table: None, values: None, columns: None, condition: None}
+        Query{operation: operation, database: None, table: None, values: None, assignments: None, file: None, update_on_conflict: false, projection: None, aggregates: Vec::new(), group_by: None, having: None, distinct: false, exists: false, columns: None, alteration: None, join: None, condition: None, order_by: None, limit: None, delete_rows: false, explained: None, offset: None, placeholders: Vec::new(), parameters: Vec::new()}
+    }
+
+    // Lexes and parses a single query, so that queries can
+    // be written out, have values bound to them and be run
+    // from Rust.
+    pub fn parse(text: &str) -> Result<Query, CoilError> {
+        let tokens = Lexer::lex(&mut Lexer::new(), String::from(text))
+            .map_err(|error| CoilError::InvalidQuery(format!("at {}: {}", error.offset, error.message)))?;
+        Parser::parse(&mut Parser::new(), tokens)
+            .map_err(|error| CoilError::InvalidQuery(format!("at token {}: {}", error.position, error.message())))
+    }
+
+    pub fn placeholder_count(&self) -> usize {
+        self.placeholders.len()
+    }
+
+    // Binds a value to the `index`th `?` (counting from 0).
+    // Values are only ever treated as values, so nothing
+    // in them can change what the query does.
+    pub fn bind(&mut self, index: usize, value: FieldValue) -> Result<(), CoilError> {
+        let parameter = self.parameters.get_mut(index).ok_or(CoilError::NoSuchPlaceholder(index))?;
+        *parameter = Some(value);
+        Ok(())
+    }
+
+    // Binds a value to every placeholder, in order.
+    pub fn bind_all(&mut self, values: Vec<FieldValue>) -> Result<(), CoilError> {
+        if values.len() != self.placeholders.len() {
+            return Err(if values.len() < self.placeholders.len() { CoilError::NotEnoughValues } else { CoilError::TooManyValues });
+        }
+        for (index, value) in values.into_iter().enumerate() {
+            self.bind(index, value)?;
+        }
+        Ok(())
+    }
+
+    // Swaps every placeholder for the value bound to it,
+    // which has to happen before the query runs.
+    pub fn resolve_placeholders(&mut self) -> Result<(), CoilError> {
+        if let Some(index) = self.parameters.iter().position(Option::is_none) {
+            return Err(CoilError::UnboundPlaceholder(index));
+        }
+        let placeholders = std::mem::take(&mut self.placeholders);
+        let parameters = std::mem::take(&mut self.parameters);
+        // An explain's placeholders are all in
+        // the query it's explaining.
+        let mut query = self;
+        while let Some(ref mut explained) = query.explained {
+            query = explained;
+        }
+        for (index, (placeholder, value)) in placeholders.into_iter().zip(parameters.into_iter().flatten()).enumerate() {
+            match placeholder {
+                Placeholder::Value(position) => {
+                    if let Some(values) = &mut query.values {
+                        values[position] = value;
+                    }
+                },
+                Placeholder::Expression => {
+                    let expressions = [query.condition.as_deref_mut(), query.having.as_deref_mut(),
+                                       query.join.as_mut().map(|join| join.condition.as_mut())];
+                    for expression in expressions.into_iter().flatten() {
+                        replace_placeholder(expression, index, &value);
+                    }
+                    for (_, expression) in query.assignments.iter_mut().flatten() {
+                        replace_placeholder(expression, index, &value);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    // The index of the offending token.
+    pub position: usize,
+    // What the parser was looking for,
+    // e.g. "`from` after the selected columns".
+    pub expected: String,
+    // `None` if the query ended early.
+    pub found: Option<Token>
+}
+
+impl ParseError {
+    pub fn message(&self) -> String {
+        let found = match &self.found {
+            Some(token) => token.describe(),
+            None => String::from("end of query")
+        };
+        format!("expected {}, found {}", self.expected, found)
     }
 }
 
 // Just your good ol' fashioned recursive descent parser.
 pub struct Parser {
     tokens: Vec<Token>,
-    previous: Option<Token>
+    previous: Option<Token>,
+    // The placeholders in the query being parsed.
+    placeholders: Vec<Placeholder>,
+    // How many tokens the query started with,
+    // for working out error positions.
+    length: usize
 }
 
 impl Parser {
     pub fn new() -> Self {
         // Placeholder value.
-        Parser{tokens: Vec::new(), previous: None}
+        Parser{tokens: Vec::new(), previous: None, placeholders: Vec::new(), length: 0}
     }
 
-    pub fn parse(parser: &mut Parser, tokens: Vec<Token>) -> Query {
+    pub fn parse(parser: &mut Parser, tokens: Vec<Token>) -> Result<Query, ParseError> {
+        parser.length = tokens.len();
         parser.tokens = tokens;
         parser.tokens.reverse();
-        parser.parse_query().unwrap()
+        parser.placeholders.clear();
+        let query = parser.parse_whole_query()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("end of query"));
+        }
+        Ok(query)
+    }
+
+    // Parses every query in `tokens`, separated by
+    // semicolons. Empty statements (like the one
+    // after a trailing `;`) are skipped.
+    pub fn parse_all(parser: &mut Parser, tokens: Vec<Token>) -> Result<Vec<Query>, ParseError> {
+        parser.length = tokens.len();
+        parser.tokens = tokens;
+        parser.tokens.reverse();
+        parser.placeholders.clear();
+        let mut queries: Vec<Query> = Vec::new();
+        loop {
+            while parser.consume(&[Token::Semicolon]) {}
+            if parser.peek().is_none() {
+                break;
+            }
+            queries.push(parser.parse_whole_query()?);
+            if parser.peek().is_some() && !parser.consume(&[Token::Semicolon]) {
+                return Err(parser.error("`;` or end of query"));
+            }
+        }
+        Ok(queries)
+    }
+
+    // `parse_query`, along with the placeholders
+    // found on the way.
+    fn parse_whole_query(&mut self) -> Result<Query, ParseError> {
+        let mut query = self.parse_query()?;
+        query.placeholders = std::mem::take(&mut self.placeholders);
+        query.parameters = vec![None; query.placeholders.len()];
+        Ok(query)
     }
 
     fn next(&mut self) -> Option<Token> {
@@ -92,6 +473,11 @@ impl Parser {
         self.tokens.last()
     }
 
+    // Looks one token further ahead than `peek`.
+    fn peek_next(&self) -> Option<&Token> {
+        self.tokens.iter().rev().nth(1)
+    }
+
     fn peek_back(&self) -> Option<&Token> {
         self.previous.as_ref()
     }
@@ -121,284 +507,710 @@ impl Parser {
         false
     }
 
-    fn parse_query(&mut self) -> Option<Query> {
-        match self.next()? {
-            Token::Get => self.parse_get_query(),
-            Token::Put => self.parse_put_query(),
-            Token::Update => self.parse_update_query(),
-            Token::Create => self.parse_create_query(),
-            Token::Delete => self.parse_delete_query(),
-            _ => None
+    // Builds an error about the next token.
+    fn error(&self, expected: &str) -> ParseError {
+        ParseError{position: self.length - self.tokens.len(),
+                   expected: String::from(expected),
+                   found: self.peek().cloned()}
+    }
+
+    fn expect(&mut self, expected: &[Token], description: &str) -> Result<(), ParseError> {
+        if self.consume(expected) {
+            return Ok(());
+        }
+        Err(self.error(description))
+    }
+
+    fn expect_identifier(&mut self, description: &str) -> Result<String, ParseError> {
+        if let Some(Token::Identifier(name)) = self.peek().cloned() {
+            let _ = self.next();
+            return Ok(name);
+        }
+        Err(self.error(description))
+    }
+
+    // A column name, optionally qualified with the
+    // name of its table, like `customers.ID`.
+    fn parse_column_name(&mut self, description: &str) -> Result<String, ParseError> {
+        let name = self.expect_identifier(description)?;
+        if self.consume(&[Token::Period]) {
+            let column = self.expect_identifier("a column name after `.`")?;
+            return Ok(format!("{}.{}", name, column));
+        }
+        Ok(name)
+    }
+
+    // A table name, optionally qualified with the name
+    // of its database, like `analytics.events`.
+    fn parse_table_name(&mut self, query: &mut Query) -> Result<(), ParseError> {
+        let name = self.expect_identifier("a table name")?;
+        if self.consume(&[Token::Period]) {
+            query.database = Some(name);
+            query.table = Some(self.expect_identifier("a table name after `.`")?);
+        }
+        else {
+            query.table = Some(name);
+        }
+        Ok(())
+    }
+
+    fn parse_query(&mut self) -> Result<Query, ParseError> {
+        let error = self.error("a query");
+        match self.next() {
+            Some(Token::Get) => self.parse_get_query(),
+            Some(Token::Put) => self.parse_put_query(),
+            Some(Token::Update) => self.parse_update_query(),
+            Some(Token::Create) => self.parse_create_query(),
+            Some(Token::Delete) => self.parse_delete_query(),
+            Some(Token::Alter) => self.parse_alter_query(),
+            Some(Token::Truncate) => {
+                let mut query = Query::new(Operation::Truncate);
+                self.parse_table_name(&mut query)?;
+                Ok(query)
+            },
+            Some(Token::Describe) => {
+                let mut query = Query::new(Operation::Describe);
+                self.parse_table_name(&mut query)?;
+                Ok(query)
+            },
+            Some(Token::Show) => {
+                self.expect(&[Token::Tables], "`tables` after `show`")?;
+                Ok(Query::new(Operation::Show))
+            },
+            Some(Token::Begin) => Ok(Query::new(Operation::Begin)),
+            Some(Token::Commit) => Ok(Query::new(Operation::Commit)),
+            Some(Token::Rollback) => Ok(Query::new(Operation::Rollback)),
+            Some(Token::Explain) => {
+                let mut query = Query::new(Operation::Explain);
+                query.explained = Some(Box::new(self.parse_query()?));
+                Ok(query)
+            },
+            _ => Err(error)
         }
     }
 
-    fn parse_create_query(&mut self) -> Option<Query> {
+    fn parse_create_query(&mut self) -> Result<Query, ParseError> {
         let mut query = Query::new(Operation::Create);
-        let keyword = self.next()?;
-        let identifier = self.next()?;
-        match identifier {
-            Token::Identifier(name) => {
-                match keyword {
-                    Token::Database => {
-                        query.database = Some(name);
-                        return Some(query);
-                    },
-                    Token::Table => { query.table = Some(name); },
-                    _ => { return None; }
-                }
-            }
-            _ => { return None; }
+        if self.consume(&[Token::Database]) {
+            query.database = Some(self.expect_identifier("a database name")?);
+            return Ok(query);
         }
+        self.expect(&[Token::Table], "`table` or `database` after `create`")?;
+        self.parse_table_name(&mut query)?;
 
         let mut columns: Vec<Column> = Vec::new();
 
-        if !self.consume(&[Token::LeftBracket]) {
-            return None;
-        }
+        self.expect(&[Token::LeftBracket], "`[` before the column list")?;
         loop {
-            let Token::Identifier(name) = self.next()? else { return None; };
+            columns.push(self.parse_column_definition()?);
 
-            if !self.consume(&[Token::Colon]) {
-                return None;
+            if !self.consume(&[Token::Comma]) {
+                self.expect(&[Token::RightBracket], "`,` or `]` after the column type")?;
+                break;
             }
+        }
+        query.columns = Some(columns);
 
-            let field_type = match self.next()? {
-                Token::NumberType => FieldType::Number,
-                Token::TextType => FieldType::Text,
-                _ => { return None; } 
-            };
+        Ok(query)
+    }
 
-            columns.push(Column::new(name, field_type));
+    // A column's name, type and modifiers, like
+    // `ID: number primary`.
+    fn parse_column_definition(&mut self) -> Result<Column, ParseError> {
+        let name = self.expect_identifier("a column name")?;
 
-            if !self.consume(&[Token::Comma]) {
-                if self.consume(&[Token::RightBracket]) {
-                    break;
-                }
-                return None;
+        self.expect(&[Token::Colon], "`:` after the column name")?;
+
+        let field_type = match self.peek() {
+            Some(Token::NumberType) => FieldType::Number,
+            Some(Token::TextType) => FieldType::Text,
+            Some(Token::BooleanType) => FieldType::Boolean,
+            _ => { return Err(self.error("a column type")); }
+        };
+        let _ = self.next();
+
+        let mut column = Column::new(name, field_type);
+        while self.consume(&[Token::Primary, Token::Unique, Token::NotNull,
+                             Token::Default, Token::Auto]) {
+            match self.peek_back() {
+                Some(Token::Primary) => { column.is_primary = true; },
+                Some(Token::Unique) => { column.is_unique = true; },
+                Some(Token::NotNull) => { column.is_not_null = true; },
+                Some(Token::Auto) => { column.is_auto = true; },
+                Some(Token::Default) => {
+                    let Some(value) = self.parse_literal() else {
+                        return Err(self.error("a value after `default`"));
+                    };
+                    column.default = Some(value);
+                },
+                _ => unreachable!()
             }
         }
-        query.columns = Some(columns);
 
-        Some(query)
+        Ok(column)
     }
 
-    fn parse_get_query(&mut self) -> Option<Query> {
-        let mut query = Query::new(Operation::Get);
+    fn parse_alter_query(&mut self) -> Result<Query, ParseError> {
+        let mut query = Query::new(Operation::Alter);
+        self.expect(&[Token::Table], "`table` after `alter`")?;
+        self.parse_table_name(&mut query)?;
 
-        if !self.consume(&[Token::Star]) {
-            return None;
+        // `column` is optional when adding or dropping
+        // one, but it's how a column is renamed instead
+        // of the table.
+        if self.consume(&[Token::Drop]) {
+            let _ = self.consume(&[Token::Column]);
+            query.alteration = Some(Alteration::DropColumn(self.expect_identifier("a column name")?));
         }
-        if !self.consume(&[Token::From]) {
-            return None;
+        else if self.consume(&[Token::Rename]) {
+            if self.consume(&[Token::Column]) {
+                let column = self.expect_identifier("a column name")?;
+                self.expect(&[Token::To], "`to` after the column name")?;
+                query.alteration = Some(Alteration::RenameColumn(column, self.expect_identifier("a new column name")?));
+            }
+            else {
+                query.alteration = Some(Alteration::RenameTable(self.expect_identifier("a new table name")?));
+            }
         }
-        let identifier = self.next()?;
-        match identifier {
-            Token::Identifier(name) => { query.table = Some(name); },
-            _ => { return None; }
+        else {
+            self.expect(&[Token::AddKeyword], "`add`, `drop` or `rename` after the table name")?;
+            let _ = self.consume(&[Token::Column]);
+            query.alteration = Some(Alteration::AddColumn(self.parse_column_definition()?));
+        }
+
+        Ok(query)
+    }
+
+    fn parse_get_query(&mut self) -> Result<Query, ParseError> {
+        let mut query = Query::new(Operation::Get);
+
+        query.exists = self.consume(&[Token::Exists]);
+        query.distinct = !query.exists && self.consume(&[Token::Distinct]);
+        if !query.exists && !self.consume(&[Token::Star]) {
+            let mut projection: Vec<(String, String)> = Vec::new();
+            loop {
+                let column = if let Some(aggregate) = self.parse_aggregate()? {
+                    let name = aggregate.name();
+                    if !query.aggregates.contains(&aggregate) {
+                        query.aggregates.push(aggregate);
+                    }
+                    name
+                }
+                else if projection.is_empty() {
+                    self.parse_column_name("`*` or a list of columns after `get`")?
+                }
+                else {
+                    self.parse_column_name("a column name after `,`")?
+                };
+                let name = if self.consume(&[Token::As]) {
+                    self.expect_identifier("an alias after `as`")?
+                }
+                else {
+                    column.clone()
+                };
+                projection.push((column, name));
+                if !self.consume(&[Token::Comma]) {
+                    break;
+                }
+            }
+            query.projection = Some(projection);
+        }
+        self.expect(&[Token::From], "`from` after the selected columns")?;
+        self.parse_table_name(&mut query)?;
+
+        if self.consume(&[Token::Join]) {
+            let table = self.expect_identifier("a table name after `join`")?;
+            self.expect(&[Token::On], "`on` after the joined table")?;
+            query.join = Some(Join{table: table, condition: self.parse_or()?});
         }
 
         if self.consume(&[Token::Where]) {
-            query.condition = self.parse_or();
+            query.condition = Some(self.parse_or()?);
         }
-                
-        Some(query)
+
+        if self.consume(&[Token::Group]) {
+            self.expect(&[Token::By], "`by` after `group`")?;
+            let mut group_by: Vec<String> = vec![self.parse_column_name("a column name")?];
+            while self.consume(&[Token::Comma]) {
+                group_by.push(self.parse_column_name("a column name")?);
+            }
+            query.group_by = Some(group_by);
+        }
+
+        if self.consume(&[Token::Having]) {
+            let having = self.parse_or()?;
+            collect_aggregates(&having, &mut query.aggregates);
+            query.having = Some(having);
+        }
+
+        if self.consume(&[Token::Order]) {
+            self.expect(&[Token::By], "`by` after `order`")?;
+            let column = self.parse_column_name("a column name")?;
+            let ascending = !self.consume(&[Token::Desc]);
+            if ascending {
+                let _ = self.consume(&[Token::Asc]);
+            }
+            query.order_by = Some((column, ascending));
+        }
+
+        if self.consume(&[Token::Limit]) {
+            match self.peek() {
+                Some(Token::Integer(number)) if *number >= 0 => { query.limit = Some(*number as usize); },
+                _ => { return Err(self.error("a row count after `limit`")); }
+            }
+            let _ = self.next();
+        }
+
+        if self.consume(&[Token::Offset]) {
+            match self.peek() {
+                Some(Token::Integer(number)) if *number >= 0 => { query.offset = Some(*number as usize); },
+                _ => { return Err(self.error("a row count after `offset`")); }
+            }
+            let _ = self.next();
+        }
+
+        Ok(query)
     }
 
-    fn parse_put_query(&mut self) -> Option<Query> {
+    fn parse_put_query(&mut self) -> Result<Query, ParseError> {
         let mut query = Query::new(Operation::Put);
-        let mut values: Vec<FieldValue> = Vec::new();
-        
-        if !self.consume(&[Token::LeftBracket]) {
-            return None;
+
+        if self.consume(&[Token::Csv]) {
+            match self.next() {
+                Some(Token::String(file)) => { query.file = Some(file); },
+                _ => { return Err(self.error("a file name after `csv`")); }
+            }
+        }
+        else if self.consume(&[Token::LeftParenthesis]) {
+            query.assignments = Some(self.parse_assignments()?);
+            self.expect(&[Token::RightParenthesis], "`,` or `)` after the value")?;
+        }
+        else {
+            query.values = Some(self.parse_values()?);
+        }
+
+        self.expect(&[Token::In], "`in` after the values")?;
+        self.parse_table_name(&mut query)?;
+
+        if self.consume(&[Token::On]) {
+            self.expect(&[Token::Conflict], "`conflict` after `on`")?;
+            self.expect(&[Token::Update], "`update` after `on conflict`")?;
+            query.update_on_conflict = true;
+        }
+
+        Ok(query)
+    }
+
+    // An aggregate function call, like `count(*)`,
+    // if that's what comes next.
+    fn parse_aggregate(&mut self) -> Result<Option<Aggregate>, ParseError> {
+        if !self.consume(&[Token::Count, Token::Sum, Token::Avg, Token::Min, Token::Max]) {
+            return Ok(None);
         }
+        let function = match self.peek_back() {
+            Some(Token::Count) => AggregateFunction::Count,
+            Some(Token::Sum) => AggregateFunction::Sum,
+            Some(Token::Avg) => AggregateFunction::Average,
+            Some(Token::Min) => AggregateFunction::Minimum,
+            Some(Token::Max) => AggregateFunction::Maximum,
+            _ => unreachable!()
+        };
+        self.expect(&[Token::LeftParenthesis], "`(` after the aggregate function")?;
+        let function = if function == AggregateFunction::Count && self.consume(&[Token::Distinct]) {
+            AggregateFunction::CountDistinct
+        }
+        else {
+            function
+        };
+        let column = if function == AggregateFunction::Count && self.consume(&[Token::Star]) {
+            None
+        }
+        else {
+            Some(self.parse_column_name("a column name")?)
+        };
+        self.expect(&[Token::RightParenthesis], "`)` after the aggregated column")?;
+        Ok(Some(Aggregate{function: function, column: column}))
+    }
+
+    // A list of columns and their values, like
+    // `Name = "jim", ID = ID + 1`.
+    fn parse_assignments(&mut self) -> Result<Vec<(String, Box<Expression>)>, ParseError> {
+        let mut assignments: Vec<(String, Box<Expression>)> = Vec::new();
         loop {
-            match self.next()? {
-                Token::Comma => continue,
-                Token::Float(number) => { values.push(FieldValue::Float(number)); },
-                Token::Integer(number) => { values.push(FieldValue::Integer(number)); },
-                Token::String(text) => { values.push(FieldValue::Text(String::from(text))); },
-                Token::None => { values.push(FieldValue::None); },
-                Token::RightBracket => { break; },
-                _ => { return None; }
+            let column = self.expect_identifier("a column name")?;
+            self.expect(&[Token::Equal], "`=` after the column name")?;
+            assignments.push((column, self.parse_or()?));
+
+            if !self.consume(&[Token::Comma]) {
+                break;
+            }
+        }
+
+        Ok(assignments)
+    }
+
+    // A bracketed list of literals, like `[1, "jim"]`.
+    fn parse_values(&mut self) -> Result<Vec<FieldValue>, ParseError> {
+        let mut values: Vec<FieldValue> = Vec::new();
+
+        self.expect(&[Token::LeftBracket], "`[` before the values")?;
+        loop {
+            if let Some(value) = self.parse_literal() {
+                values.push(value);
+            }
+            else if self.consume(&[Token::Placeholder]) {
+                self.placeholders.push(Placeholder::Value(values.len()));
+                values.push(FieldValue::None);
+            }
+            else if self.consume(&[Token::RightBracket]) {
+                break;
+            }
+            else if !self.consume(&[Token::Comma]) {
+                return Err(self.error("a value or `]`"));
             }
         }
-        query.values = Some(values);
 
-        if !self.consume(&[Token::In]) {
-            return None;
+        Ok(values)
+    }
+
+    // `parse_values`, but for the list of an `in`, where
+    // placeholders belong to the condition rather than
+    // to the query's values.
+    fn parse_list(&mut self) -> Result<Vec<Expression>, ParseError> {
+        let mut values: Vec<Expression> = Vec::new();
+
+        self.expect(&[Token::LeftBracket], "`[` before the values")?;
+        loop {
+            if let Some(value) = self.parse_literal() {
+                values.push(Expression{expression_type: value.to_expression_type(),
+                    l_operand: None, r_operand: None});
+            }
+            else if self.consume(&[Token::Placeholder]) {
+                self.placeholders.push(Placeholder::Expression);
+                values.push(Expression{expression_type: ExpressionType::Placeholder(self.placeholders.len() - 1),
+                    l_operand: None, r_operand: None});
+            }
+            else if self.consume(&[Token::RightBracket]) {
+                break;
+            }
+            else if !self.consume(&[Token::Comma]) {
+                return Err(self.error("a value or `]`"));
+            }
         }
 
-        query.table = match self.next()? {
-            Token::Identifier(name) => Some(name),
-            _ => None
-        };
+        Ok(values)
+    }
 
-        Some(query)
+    // Consumes the next token if it's a literal. A `-`
+    // followed by a number is a negative literal.
+    fn parse_literal(&mut self) -> Option<FieldValue> {
+        if self.check(&[Token::Subtract]) {
+            let value = match self.peek_next()? {
+                Token::Integer(number) => FieldValue::Integer(-number),
+                Token::Float(number) => FieldValue::Float(-number),
+                _ => { return None; }
+            };
+            let _ = self.next();
+            let _ = self.next();
+            return Some(value);
+        }
+        let value = match self.peek()? {
+            Token::Float(number) => FieldValue::Float(*number),
+            Token::Integer(number) => FieldValue::Integer(*number),
+            Token::String(text) => FieldValue::Text(text.clone()),
+            Token::Boolean(value) => FieldValue::Boolean(*value),
+            Token::None => FieldValue::None,
+            _ => { return None; }
+        };
+        let _ = self.next();
+        Some(value)
     }
 
-    fn parse_update_query(&mut self) -> Option<Query> {
+    fn parse_update_query(&mut self) -> Result<Query, ParseError> {
         let mut query = Query::new(Operation::Update);
-        todo!("update queries");
-        Some(query)
+        self.parse_table_name(&mut query)?;
+        self.expect(&[Token::Set], "`set` after the table name")?;
+
+        query.assignments = Some(self.parse_assignments()?);
+
+        if self.consume(&[Token::Where]) {
+            query.condition = Some(self.parse_or()?);
+        }
+        Ok(query)
     }
 
-    fn parse_delete_query(&mut self) -> Option<Query> {
+    fn parse_delete_query(&mut self) -> Result<Query, ParseError> {
         let mut query = Query::new(Operation::Delete);
-        let keyword = self.next()?;
-        let identifier = self.next()?;
-        match identifier {
-            Token::Identifier(name) => {
-                match keyword {
-                    Token::Database => { query.database = Some(name); },
-                    Token::Table => { query.table = Some(name); },
-                    _ => { return None; }
-                }
+        if self.consume(&[Token::Database]) {
+            query.database = Some(self.expect_identifier("a database name")?);
+        }
+        else if self.consume(&[Token::From]) {
+            query.delete_rows = true;
+            self.parse_table_name(&mut query)?;
+            if self.consume(&[Token::Where]) {
+                query.condition = Some(self.parse_or()?);
             }
-            _ => { return None; }
         }
-        todo!("delete queries");
-        Some(query)
+        else {
+            self.expect(&[Token::Table], "`from`, `table` or `database` after `delete`")?;
+            self.parse_table_name(&mut query)?;
+        }
+        Ok(query)
     }
 
-    fn parse_or(&mut self) -> Option<Box<Expression>> {
-        let mut expression = self.parse_and();
+    fn parse_or(&mut self) -> Result<Box<Expression>, ParseError> {
+        let mut expression = self.parse_xor()?;
 
         while self.consume(&[Token::Or]) {
-            let expression_type = match *self.peek_back()? {
-                Token::Or => ExpressionType::Or,
-                _ => { return None; }
+            let expression_type = match self.peek_back() {
+                Some(Token::Or) => ExpressionType::Or,
+                _ => unreachable!()
             };
-            let r_expression = self.parse_and();
-            expression = Some(Box::new(
+            let r_expression = self.parse_xor()?;
+            expression = Box::new(
                 Expression{expression_type: expression_type,
-                           l_operand: expression,
-                           r_operand: r_expression}));
+                           l_operand: Some(expression),
+                           r_operand: Some(r_expression)});
         }
 
-        expression
+        Ok(expression)
     }
 
-    fn parse_and(&mut self) -> Option<Box<Expression>> {
-        let mut expression = self.parse_equality();
+    fn parse_xor(&mut self) -> Result<Box<Expression>, ParseError> {
+        let mut expression = self.parse_and()?;
 
-        while self.consume(&[Token::And]) {
-            let expression_type = match *self.peek_back()? {
-                Token::And => ExpressionType::And,
-                _ => { return None; }
+        while self.consume(&[Token::Xor]) {
+            let expression_type = match self.peek_back() {
+                Some(Token::Xor) => ExpressionType::Xor,
+                _ => unreachable!()
             };
-            let r_expression = self.parse_equality();
-            expression = Some(Box::new(
+            let r_expression = self.parse_and()?;
+            expression = Box::new(
                 Expression{expression_type: expression_type,
-                           l_operand: expression,
-                           r_operand: r_expression}));
+                           l_operand: Some(expression),
+                           r_operand: Some(r_expression)});
         }
 
-        expression
+        Ok(expression)
     }
 
-    fn parse_equality(&mut self) -> Option<Box<Expression>> {
-        let mut expression = self.parse_comparison();
-        
-        while self.consume(&[Token::Equal, Token::NotEqual]) {
-            let expression_type = match *self.peek_back()? {
-                Token::Equal => ExpressionType::Equal,
-                Token::NotEqual => ExpressionType::NotEqual,
-                _ => { return None; }
+    fn parse_and(&mut self) -> Result<Box<Expression>, ParseError> {
+        let mut expression = self.parse_not()?;
+
+        while self.consume(&[Token::And]) {
+            let expression_type = match self.peek_back() {
+                Some(Token::And) => ExpressionType::And,
+                _ => unreachable!()
             };
-            let r_expression = self.parse_comparison();
-            expression = Some(Box::new(
+            let r_expression = self.parse_not()?;
+            expression = Box::new(
                 Expression{expression_type: expression_type,
-                    l_operand: expression,
-                    r_operand: r_expression}));
+                           l_operand: Some(expression),
+                           r_operand: Some(r_expression)});
+        }
+
+        Ok(expression)
+    }
+
+    // A `not` in front of a condition negates the whole
+    // condition, so `not ID = 1` is `not (ID = 1)`. Inside
+    // a value it's an ordinary unary operator instead.
+    fn parse_not(&mut self) -> Result<Box<Expression>, ParseError> {
+        if self.consume(&[Token::Not]) {
+            let operand = self.parse_not()?;
+            return Ok(Box::new(
+                Expression{expression_type: ExpressionType::Not,
+                           l_operand: Some(operand),
+                           r_operand: None}));
+        }
+
+        self.parse_equality()
+    }
+
+    fn parse_equality(&mut self) -> Result<Box<Expression>, ParseError> {
+        let mut expression = self.parse_comparison()?;
+
+        loop {
+            if self.check(&[Token::In]) && self.peek_next() == Some(&Token::LeftBracket) {
+                let _ = self.next();
+                let values = self.parse_list()?;
+                expression = Box::new(
+                    Expression{expression_type: ExpressionType::In(values),
+                               l_operand: Some(expression),
+                               r_operand: None});
+                continue;
+            }
+            if self.consume(&[Token::Is]) {
+                let expression_type = if self.consume(&[Token::Not]) {
+                    ExpressionType::IsNotNone
                 }
-        expression
+                else {
+                    ExpressionType::IsNone
+                };
+                self.expect(&[Token::None], "`none` after `is`")?;
+                expression = Box::new(
+                    Expression{expression_type: expression_type,
+                               l_operand: Some(expression),
+                               r_operand: None});
+                continue;
+            }
+            if !self.consume(&[Token::Equal, Token::NotEqual, Token::EqualIgnoringCase, Token::Like]) {
+                break;
+            }
+            let expression_type = match self.peek_back() {
+                Some(Token::Equal) => ExpressionType::Equal,
+                Some(Token::NotEqual) => ExpressionType::NotEqual,
+                Some(Token::EqualIgnoringCase) => ExpressionType::EqualIgnoringCase,
+                Some(Token::Like) => ExpressionType::Like,
+                _ => unreachable!()
+            };
+            let r_expression = self.parse_comparison()?;
+            expression = Box::new(
+                Expression{expression_type: expression_type,
+                           l_operand: Some(expression),
+                           r_operand: Some(r_expression)});
+        }
+
+        Ok(expression)
     }
 
-    fn parse_comparison(&mut self) -> Option<Box<Expression>> {
-        let mut expression = self.parse_term();
+    fn parse_comparison(&mut self) -> Result<Box<Expression>, ParseError> {
+        let mut expression = self.parse_term()?;
+
+        // `x between lo and hi` is just shorthand
+        // for `x >= lo and x <= hi`.
+        if self.consume(&[Token::Between]) {
+            let low = self.parse_term()?;
+            self.expect(&[Token::And], "`and` between the bounds of `between`")?;
+            let high = self.parse_term()?;
+            let above_low = Box::new(
+                Expression{expression_type: ExpressionType::GreaterThanOrEqual,
+                           l_operand: Some(expression.clone()),
+                           r_operand: Some(low)});
+            let below_high = Box::new(
+                Expression{expression_type: ExpressionType::LessThanOrEqual,
+                           l_operand: Some(expression),
+                           r_operand: Some(high)});
+            return Ok(Box::new(
+                Expression{expression_type: ExpressionType::And,
+                           l_operand: Some(above_low),
+                           r_operand: Some(below_high)}));
+        }
 
         while self.consume(&[Token::GreaterThan, Token::GreaterThanOrEqual,
                              Token::LessThan, Token::LessThanOrEqual]) {
-            let expression_type = match *self.peek_back()? {
-                Token::GreaterThan => ExpressionType::GreaterThan,
-                Token::GreaterThanOrEqual => ExpressionType::GreaterThanOrEqual,
-                Token::LessThan => ExpressionType::LessThan,
-                Token::LessThanOrEqual => ExpressionType::LessThanOrEqual,
-                _ => { return None; }
+            let expression_type = match self.peek_back() {
+                Some(Token::GreaterThan) => ExpressionType::GreaterThan,
+                Some(Token::GreaterThanOrEqual) => ExpressionType::GreaterThanOrEqual,
+                Some(Token::LessThan) => ExpressionType::LessThan,
+                Some(Token::LessThanOrEqual) => ExpressionType::LessThanOrEqual,
+                _ => unreachable!()
             };
-            let r_expression = self.parse_term();
-            expression = Some(Box::new(
+            let r_expression = self.parse_term()?;
+            expression = Box::new(
                 Expression{expression_type: expression_type,
-                           l_operand: expression,
-                           r_operand: r_expression}));
+                           l_operand: Some(expression),
+                           r_operand: Some(r_expression)});
         }
 
-        expression
+        Ok(expression)
     }
 
-    fn parse_term(&mut self) -> Option<Box<Expression>> {
-        let mut expression = self.parse_factor();
+    fn parse_term(&mut self) -> Result<Box<Expression>, ParseError> {
+        let mut expression = self.parse_factor()?;
 
         while self.consume(&[Token::Add, Token::Subtract]) {
-            let expression_type = match *self.peek_back()? {
-                Token::Add => ExpressionType::Add,
-                Token::Subtract => ExpressionType::Subtract,
-                _ => { return None; }
+            let expression_type = match self.peek_back() {
+                Some(Token::Add) => ExpressionType::Add,
+                Some(Token::Subtract) => ExpressionType::Subtract,
+                _ => unreachable!()
             };
-            let r_expression = self.parse_factor();
-            expression = Some(Box::new(
+            let r_expression = self.parse_factor()?;
+            expression = Box::new(
                 Expression{expression_type: expression_type,
-                           l_operand: expression,
-                           r_operand: r_expression}));
+                           l_operand: Some(expression),
+                           r_operand: Some(r_expression)});
         }
 
-        expression
+        Ok(expression)
     }
 
-    fn parse_factor(&mut self) -> Option<Box<Expression>> {
-        let mut expression = self.parse_unary();
+    fn parse_factor(&mut self) -> Result<Box<Expression>, ParseError> {
+        let mut expression = self.parse_unary()?;
 
-        while self.consume(&[Token::Star, Token::Divide,
-                             Token::Power, Token::Modulus]) {
-            let expression_type = match *self.peek_back()? {
-                Token::Star => ExpressionType::Multiply,
-                Token::Divide => ExpressionType::Divide,
-                Token::Power => ExpressionType::Power,
-                Token::Modulus => ExpressionType::Modulus,
-                _ => { return None; }
+        while self.consume(&[Token::Star, Token::Divide, Token::Modulus]) {
+            let expression_type = match self.peek_back() {
+                Some(Token::Star) => ExpressionType::Multiply,
+                Some(Token::Divide) => ExpressionType::Divide,
+                Some(Token::Modulus) => ExpressionType::Modulus,
+                _ => unreachable!()
             };
-            let r_expression = self.parse_unary();
-            expression = Some(Box::new(
+            let r_expression = self.parse_unary()?;
+            expression = Box::new(
                 Expression{expression_type: expression_type,
-                           l_operand: expression,
-                           r_operand: r_expression}));
+                           l_operand: Some(expression),
+                           r_operand: Some(r_expression)});
         }
 
-        expression
+        Ok(expression)
     }
 
-    fn parse_unary(&mut self) -> Option<Box<Expression>> {
-        let mut expression = self.parse_primary();
-
-        while self.consume(&[Token::Not, Token::Add, Token::Subtract]) {
-            let expression_type = match *self.peek_back()? {
-                Token::Not => ExpressionType::Not,
-                Token::Add => ExpressionType::Positive,
-                Token::Subtract => ExpressionType::Negate,
-                _ => { return None; }
+    fn parse_unary(&mut self) -> Result<Box<Expression>, ParseError> {
+        if self.consume(&[Token::Not, Token::Add, Token::Subtract]) {
+            let expression_type = match self.peek_back() {
+                Some(Token::Not) => ExpressionType::Not,
+                Some(Token::Add) => ExpressionType::Positive,
+                Some(Token::Subtract) => ExpressionType::Negate,
+                _ => unreachable!()
             };
-            let r_expression = self.parse_primary();
-            expression = Some(Box::new(
+            // Unary expressions only use `l_operand`.
+            let operand = self.parse_unary()?;
+            return Ok(Box::new(
                 Expression{expression_type: expression_type,
-                           l_operand: expression,
-                           r_operand: r_expression}));
+                           l_operand: Some(operand),
+                           r_operand: None}));
         }
 
-        expression
+        self.parse_power()
     }
 
-    fn parse_primary(&mut self) -> Option<Box<Expression>> {
-        let mut expression: Option<Box<Expression>> = None;
+    // Powers bind tighter than anything else, and group
+    // from the right like they do in maths, so `2 ^ 3 ^ 2`
+    // is `2 ^ 9` and `-2 ^ 2` is `-(2 ^ 2)`. The exponent
+    // can still be negated, as in `2 ^ -1`.
+    fn parse_power(&mut self) -> Result<Box<Expression>, ParseError> {
+        let expression = self.parse_primary()?;
+
+        if self.consume(&[Token::Power]) {
+            let r_expression = self.parse_unary()?;
+            return Ok(Box::new(
+                Expression{expression_type: ExpressionType::Power,
+                           l_operand: Some(expression),
+                           r_operand: Some(r_expression)}));
+        }
+
+        Ok(expression)
+    }
+
+    fn parse_primary(&mut self) -> Result<Box<Expression>, ParseError> {
+        if self.consume(&[Token::LeftParenthesis]) {
+            let grouped_expression = self.parse_or()?;
+            self.expect(&[Token::RightParenthesis], "`)` after the grouped expression")?;
+            return Ok(grouped_expression);
+        }
+
+        if let Some(aggregate) = self.parse_aggregate()? {
+            return Ok(Box::new(
+                Expression{expression_type: ExpressionType::Aggregate(aggregate),
+                    l_operand: None, r_operand: None}));
+        }
+
+        if self.consume(&[Token::Placeholder]) {
+            self.placeholders.push(Placeholder::Expression);
+            return Ok(Box::new(
+                Expression{expression_type: ExpressionType::Placeholder(self.placeholders.len() - 1),
+                    l_operand: None, r_operand: None}));
+        }
 
         let is_primary_type = |token: &Token| {
             match *token {
@@ -406,37 +1218,295 @@ impl Parser {
                 | Token::Integer(_)
                 | Token::Float(_)
                 | Token::String(_)
+                | Token::Boolean(_)
                 | Token::Identifier(_) => true,
                 _ => false
             }
         };
 
-        while is_primary_type(self.peek()?) {
-            let next = self.next();
-            let expression_type = match next? {
-                Token::None => Some(ExpressionType::None),
-                Token::Integer(number) => Some(ExpressionType::Integer(number)),
-                Token::Float(number) => Some(ExpressionType::Float(number)),
-                Token::String(string) => Some(ExpressionType::String(string)),
-                Token::Identifier(identifier) => Some(ExpressionType::Identifier(identifier)),
+        if self.peek().is_some_and(is_primary_type) {
+            let expression_type = match self.next() {
+                Some(Token::None) => Some(ExpressionType::None),
+                Some(Token::Integer(number)) => Some(ExpressionType::Integer(number)),
+                Some(Token::Float(number)) => Some(ExpressionType::Float(number)),
+                Some(Token::String(string)) => Some(ExpressionType::String(string)),
+                Some(Token::Boolean(value)) => Some(ExpressionType::Boolean(value)),
+                Some(Token::Identifier(identifier)) => {
+                    if self.consume(&[Token::Period]) {
+                        let column = self.expect_identifier("a column name after `.`")?;
+                        Some(ExpressionType::Identifier(format!("{}.{}", identifier, column)))
+                    }
+                    else {
+                        Some(ExpressionType::Identifier(identifier))
+                    }
+                },
                 _ => None
             };
 
-            if expression_type.is_none() && *self.peek()? == Token::LeftParenthesis {
-                let grouped_expression = self.parse_or();
-                if !self.consume(&[Token::RightParenthesis]) {
-                    return None;
-                }
-                expression = grouped_expression;
-                return expression;
-            }
-            else {
-                expression = Some(Box::new(
-                    Expression{expression_type: expression_type.unwrap(),
-                        l_operand: None, r_operand: None}));
-                return expression;
-            }
+            return Ok(Box::new(
+                Expression{expression_type: expression_type.unwrap(),
+                    l_operand: None, r_operand: None}));
+        }
+        Err(self.error("a value"))
+    }
+}
+
+// Adds the aggregates in `expression` that
+// aren't in `aggregates` yet.
+fn collect_aggregates(expression: &Expression, aggregates: &mut Vec<Aggregate>) {
+    if let ExpressionType::Aggregate(aggregate) = &expression.expression_type {
+        if !aggregates.contains(aggregate) {
+            aggregates.push(aggregate.clone());
+        }
+    }
+    for operand in [&expression.l_operand, &expression.r_operand].into_iter().flatten() {
+        collect_aggregates(operand, aggregates);
+    }
+}
+
+// Swaps the `index`th placeholder in `expression`
+// for `value`.
+fn replace_placeholder(expression: &mut Expression, index: usize, value: &FieldValue) {
+    if expression.expression_type == ExpressionType::Placeholder(index) {
+        expression.expression_type = value.to_expression_type();
+    }
+    if let ExpressionType::In(candidates) = &mut expression.expression_type {
+        for candidate in candidates {
+            replace_placeholder(candidate, index, value);
         }
-        None
+    }
+    for operand in [&mut expression.l_operand, &mut expression.r_operand].into_iter().flatten() {
+        replace_placeholder(operand, index, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Query, ParseError> {
+        let tokens = Lexer::lex(&mut Lexer::new(), String::from(text)).unwrap();
+        Parser::parse(&mut Parser::new(), tokens)
+    }
+
+    fn parse_all(text: &str) -> Result<Vec<Query>, ParseError> {
+        let tokens = Lexer::lex(&mut Lexer::new(), String::from(text)).unwrap();
+        Parser::parse_all(&mut Parser::new(), tokens)
+    }
+
+    fn condition(text: &str) -> Box<Expression> {
+        parse(&format!("get * from t where {}", text)).unwrap().condition.unwrap()
+    }
+
+    fn unary(expression_type: ExpressionType, operand: Box<Expression>) -> Box<Expression> {
+        Box::new(Expression{expression_type: expression_type,
+            l_operand: Some(operand), r_operand: None})
+    }
+
+    fn binary(expression_type: ExpressionType, l_operand: Box<Expression>, r_operand: Box<Expression>) -> Box<Expression> {
+        Expression::binary(expression_type, l_operand, r_operand)
+    }
+
+    fn a() -> Box<Expression> {
+        Expression::column("a")
+    }
+
+    fn number(n: i64) -> Box<Expression> {
+        Expression::value(n)
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        assert_eq!(condition("a + 2 * 3 > 0"),
+                   binary(ExpressionType::GreaterThan,
+                          binary(ExpressionType::Add, a(),
+                                 binary(ExpressionType::Multiply, number(2), number(3))),
+                          number(0)));
+    }
+
+    #[test]
+    fn binary_operators_group_from_the_left() {
+        assert_eq!(condition("a - 2 - 3 = 0"),
+                   binary(ExpressionType::Equal,
+                          binary(ExpressionType::Subtract,
+                                 binary(ExpressionType::Subtract, a(), number(2)),
+                                 number(3)),
+                          number(0)));
+        assert_eq!(condition("a / 2 % 3 = 0"),
+                   binary(ExpressionType::Equal,
+                          binary(ExpressionType::Modulus,
+                                 binary(ExpressionType::Divide, a(), number(2)),
+                                 number(3)),
+                          number(0)));
+    }
+
+    #[test]
+    fn powers_group_from_the_right_and_bind_tighter_than_unary_minus() {
+        assert_eq!(condition("a ^ 2 ** 3 = 0"),
+                   binary(ExpressionType::Equal,
+                          binary(ExpressionType::Power, a(),
+                                 binary(ExpressionType::Power, number(2), number(3))),
+                          number(0)));
+        assert_eq!(condition("-a ^ 2 = 0"),
+                   binary(ExpressionType::Equal,
+                          unary(ExpressionType::Negate, binary(ExpressionType::Power, a(), number(2))),
+                          number(0)));
+        assert_eq!(condition("a ^ -2 = 0"),
+                   binary(ExpressionType::Equal,
+                          binary(ExpressionType::Power, a(), unary(ExpressionType::Negate, number(2))),
+                          number(0)));
+    }
+
+    #[test]
+    fn unary_operators_nest() {
+        assert_eq!(condition("- + a = 0"),
+                   binary(ExpressionType::Equal,
+                          unary(ExpressionType::Negate, unary(ExpressionType::Positive, a())),
+                          number(0)));
+        assert_eq!(condition("not !a"), Expression::not(Expression::not(a())));
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        assert_eq!(condition("(a + 2) * 3 = 0"),
+                   binary(ExpressionType::Equal,
+                          binary(ExpressionType::Multiply,
+                                 binary(ExpressionType::Add, a(), number(2)),
+                                 number(3)),
+                          number(0)));
+        assert_eq!(condition("(a = 1 or a = 2) and a = 3"),
+                   Expression::and(Expression::or(Expression::eq("a", 1), Expression::eq("a", 2)),
+                                   Expression::eq("a", 3)));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_xor_and_or() {
+        assert_eq!(condition("a = 1 or a = 2 and a = 3"),
+                   Expression::or(Expression::eq("a", 1),
+                                  Expression::and(Expression::eq("a", 2), Expression::eq("a", 3))));
+        assert_eq!(condition("a = 1 xor a = 2 and a = 3 or a = 4"),
+                   Expression::or(binary(ExpressionType::Xor, Expression::eq("a", 1),
+                                         Expression::and(Expression::eq("a", 2), Expression::eq("a", 3))),
+                                  Expression::eq("a", 4)));
+        assert_eq!(condition("not a = 1 and a = 2"),
+                   Expression::and(Expression::not(Expression::eq("a", 1)), Expression::eq("a", 2)));
+    }
+
+    #[test]
+    fn comparisons_bind_tighter_than_equality() {
+        assert_eq!(condition("a < 1 = a > 2"),
+                   binary(ExpressionType::Equal, Expression::lt("a", 1), Expression::gt("a", 2)));
+    }
+
+    #[test]
+    fn add_is_only_a_keyword_in_alter_table() {
+        assert!(parse("alter table t add column x: number").is_ok());
+        assert!(parse("alter table t add x: number").is_ok());
+        assert_eq!(parse("get * from t where x add 1 > 2").unwrap_err().found, Some(Token::AddKeyword));
+    }
+
+    #[test]
+    fn selected_columns_are_separated_by_commas() {
+        let query = parse("get Name, ID as id, count(*) from customers").unwrap();
+        assert_eq!(query.projection.unwrap(), [(String::from("Name"), String::from("Name")),
+                                               (String::from("ID"), String::from("id")),
+                                               (String::from("count(*)"), String::from("count(*)"))]);
+    }
+
+    #[test]
+    fn selected_columns_without_commas_are_an_error() {
+        let error = parse("get Name ID from customers").unwrap_err();
+        assert_eq!(error.found, Some(Token::Identifier(String::from("ID"))));
+        assert_eq!(error.message(), "expected `from` after the selected columns, found identifier `ID`");
+    }
+
+    #[test]
+    fn a_trailing_comma_after_the_selected_columns_is_an_error() {
+        let error = parse("get Name, from customers").unwrap_err();
+        assert_eq!(error.position, 3);
+        assert_eq!(error.found, Some(Token::From));
+    }
+
+    #[test]
+    fn nothing_selected_is_an_error() {
+        assert_eq!(parse("get from customers").unwrap_err().found, Some(Token::From));
+    }
+
+    #[test]
+    fn errors_say_which_token_is_wrong() {
+        let error = parse("get * customers").unwrap_err();
+        assert_eq!(error.position, 2);
+        assert_eq!(error.message(), "expected `from` after the selected columns, found identifier `customers`");
+
+        let error = parse("get * from customers where").unwrap_err();
+        assert_eq!(error.position, 5);
+        assert_eq!(error.found, None);
+
+        let error = parse("get * from customers customers").unwrap_err();
+        assert_eq!(error.position, 4);
+        assert_eq!(error.expected, "end of query");
+    }
+
+    #[test]
+    fn an_empty_query_is_an_error() {
+        assert_eq!(parse("").unwrap_err().found, None);
+    }
+
+    #[test]
+    fn parentheses_nest() {
+        assert_eq!(condition("((a = 1))"), Expression::eq("a", 1));
+        assert_eq!(condition("(((a = 1) or ((a = 2))) and (not (a = 3)))"),
+                   Expression::and(Expression::or(Expression::eq("a", 1), Expression::eq("a", 2)),
+                                   Expression::not(Expression::eq("a", 3))));
+        assert_eq!(condition("((((a + 1)))) = 2"),
+                   binary(ExpressionType::Equal, binary(ExpressionType::Add, a(), number(1)), number(2)));
+    }
+
+    #[test]
+    fn unbalanced_parentheses_are_an_error() {
+        assert_eq!(parse("get * from t where (a = 1").unwrap_err().found, None);
+        assert_eq!(parse("get * from t where ((a = 1)").unwrap_err().found, None);
+        assert_eq!(parse("get * from t where (a = 1))").unwrap_err().found, Some(Token::RightParenthesis));
+        assert_eq!(parse("get * from t where ()").unwrap_err().found, Some(Token::RightParenthesis));
+    }
+
+    #[test]
+    fn statements_are_separated_by_semicolons() {
+        let queries = parse_all("get * from a; put [1] in b;delete from c").unwrap();
+        let operations: Vec<&Operation> = queries.iter().map(|query| &query.operation).collect();
+        assert_eq!(operations, [&Operation::Get, &Operation::Put, &Operation::Delete]);
+        assert_eq!(queries[2].table, Some(String::from("c")));
+    }
+
+    #[test]
+    fn empty_statements_are_skipped() {
+        assert_eq!(parse_all("get * from a;").unwrap().len(), 1);
+        assert_eq!(parse_all(";; get * from a ;;; get * from b ;").unwrap().len(), 2);
+        assert_eq!(parse_all("").unwrap().len(), 0);
+        assert_eq!(parse_all(";").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn statements_without_a_semicolon_between_them_are_an_error() {
+        let error = parse_all("get * from a get * from b").unwrap_err();
+        assert_eq!(error.position, 4);
+        assert_eq!(error.expected, "`;` or end of query");
+    }
+
+    #[test]
+    fn a_minus_before_a_number_literal_makes_it_negative() {
+        assert_eq!(parse("put [-5, - 2.5, 3] in t").unwrap().values.unwrap(),
+                   [FieldValue::Integer(-5), FieldValue::Float(-2.5), FieldValue::Integer(3)]);
+        assert_eq!(condition("a - 5 = -5"),
+                   binary(ExpressionType::Equal, binary(ExpressionType::Subtract, a(), number(5)),
+                          unary(ExpressionType::Negate, number(5))));
+    }
+
+    #[test]
+    fn xor_groups_from_the_left() {
+        assert_eq!(condition("a = 1 xor a = 2 xor a = 3"),
+                   binary(ExpressionType::Xor,
+                          binary(ExpressionType::Xor, Expression::eq("a", 1), Expression::eq("a", 2)),
+                          Expression::eq("a", 3)));
     }
 }
@@ -0,0 +1,102 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn sum_and_avg_add_up_a_number_column() {
+    let mut database = customers();
+    assert_eq!(rows(&mut database, "get sum(ID), avg(ID) from customers").unwrap(), vec![row![6, 2.0]]);
+}
+
+#[test]
+fn count_min_and_max_work_on_any_column() {
+    let mut database = customers();
+    assert_eq!(rows(&mut database, "get count(*), min(ID), max(ID) from customers").unwrap(), vec![row![3, 1, 3]]);
+    assert_eq!(rows(&mut database, "get count(Name), min(Name), max(Name) from customers").unwrap(),
+               vec![row![3, "james", "jimmy"]]);
+    assert_eq!(rows(&mut database, "get count(*), max(ID) from customers where ID > 1").unwrap(), vec![row![2, 3]]);
+}
+
+#[test]
+fn aggregates_over_a_missing_column_are_an_error() {
+    let mut database = customers();
+    assert!(matches!(rows(&mut database, "get max(age) from customers"), Err(CoilError::ColumnDoesntExist(_))));
+}
+
+#[test]
+fn sum_and_avg_of_a_text_column_are_mismatched_types() {
+    let mut database = customers();
+    for query in ["get sum(Name) from customers", "get avg(Name) from customers"] {
+        match rows(&mut database, query) {
+            Err(CoilError::MismatchedTypes{column, expected: FieldType::Number, got}) => {
+                assert_eq!(column, "Name");
+                assert_eq!(got, FieldValue::from("james"));
+            },
+            other => { panic!("{}: {:?}", query, other); }
+        }
+    }
+}
+
+#[test]
+fn aggregates_skip_nones() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table scores [n: number]").unwrap();
+    for value in ["4", "none", "2", "none", "6.0"] {
+        execute(&mut database, &format!("put [{}] in scores", value)).unwrap();
+    }
+    assert_eq!(rows(&mut database, "get count(*), count(n), sum(n), avg(n), min(n), max(n) from scores").unwrap(),
+               vec![row![5, 3, 12, 4, 2, 6]]);
+}
+
+#[test]
+fn aggregates_over_only_nones_are_none() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table scores [n: number]").unwrap();
+    execute(&mut database, "put [none] in scores").unwrap();
+    assert_eq!(rows(&mut database, "get count(*), count(n), sum(n), avg(n), min(n), max(n) from scores").unwrap(),
+               vec![row![1, 0, FieldValue::None, FieldValue::None, FieldValue::None, FieldValue::None]]);
+    execute(&mut database, "delete from scores").unwrap();
+    assert_eq!(rows(&mut database, "get count(*), sum(n) from scores").unwrap(), vec![row![0, FieldValue::None]]);
+}
+
+fn orders() -> Database {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table orders [status: text, total: number]").unwrap();
+    for (status, total) in [("new", 5), ("new", 50), ("new", 60), ("paid", 70), ("paid", 80), ("sent", 90)] {
+        execute(&mut database, &format!("put [\"{}\", {}] in orders", status, total)).unwrap();
+    }
+    database
+}
+
+#[test]
+fn having_filters_groups_and_where_filters_rows() {
+    let mut database = orders();
+    assert_eq!(rows(&mut database, "get status, count(*) from orders group by status having count(*) > 1").unwrap(),
+               vec![row!["new", 3], row!["paid", 2]]);
+    // `where` drops the small order before grouping,
+    // so "new" only has two rows left to count.
+    assert_eq!(rows(&mut database, "get status, count(*) from orders where total > 10 group by status having count(*) > 2").unwrap(),
+               Vec::<Vec<FieldValue>>::new());
+    assert_eq!(rows(&mut database, "get status, count(*) from orders where total > 10 group by status having count(*) > 1").unwrap(),
+               vec![row!["new", 2], row!["paid", 2]]);
+    // `having` can use an aggregate that isn't selected.
+    assert_eq!(rows(&mut database, "get status from orders where total > 10 group by status having sum(total) > 120").unwrap(),
+               vec![row!["paid"]]);
+}
+
+#[test]
+fn count_distinct_counts_different_values() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table orders [status: text]").unwrap();
+    for status in ["\"new\"", "\"paid\"", "\"new\"", "none", "\"new\"", "none"] {
+        execute(&mut database, &format!("put [{}] in orders", status)).unwrap();
+    }
+    // `none`s aren't counted by anything but `count(*)`.
+    assert_eq!(rows(&mut database, "get count(*), count(status), count(distinct status) from orders").unwrap(),
+               vec![row![6, 4, 2]]);
+    assert_eq!(rows(&mut database, "get count(distinct status) from orders where status = \"paid\"").unwrap(),
+               vec![row![1]]);
+    assert_eq!(rows(&mut database, "get count(distinct status) from orders where status is none").unwrap(),
+               vec![row![0]]);
+}
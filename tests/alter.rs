@@ -0,0 +1,87 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn added_columns_fill_in_the_existing_rows() {
+    let mut database = customers();
+    execute(&mut database, "alter table customers add column age: number").unwrap();
+    execute(&mut database, "alter table customers add status: text default \"new\"").unwrap();
+    execute(&mut database, "alter table customers add column n: number auto").unwrap();
+    execute(&mut database, "put [\"bob\", 4, 40, \"old\"] in customers").unwrap();
+    assert_eq!(columns(&mut database, "get * from customers").unwrap(), vec!["Name", "ID", "age", "status", "n"]);
+    assert_eq!(rows(&mut database, "get * from customers").unwrap(),
+               vec![row!["james", 1, FieldValue::None, "new", 1],
+                    row!["jim", 2, FieldValue::None, "new", 2],
+                    row!["jimmy", 3, FieldValue::None, "new", 3],
+                    row!["bob", 4, 40, "old", 4]]);
+}
+
+#[test]
+fn added_columns_have_to_fit_the_existing_rows() {
+    let mut database = customers();
+    assert!(matches!(execute(&mut database, "alter table customers add column ID: number"),
+                     Err(CoilError::ColumnAlreadyExists(_))));
+    assert!(matches!(execute(&mut database, "alter table customers add column age: number notnull"),
+                     Err(CoilError::NullConstraintViolation(_))));
+    assert_eq!(columns(&mut database, "get * from customers").unwrap(), vec!["Name", "ID"]);
+}
+
+#[test]
+fn dropped_columns_take_their_values_with_them() {
+    let mut database = customers();
+    execute(&mut database, "alter table customers add column age: number default 30").unwrap();
+    execute(&mut database, "alter table customers drop column ID").unwrap();
+    assert_eq!(columns(&mut database, "get * from customers").unwrap(), vec!["Name", "age"]);
+    assert_eq!(rows(&mut database, "get * from customers where Name = \"jim\"").unwrap(), vec![row!["jim", 30]]);
+    execute(&mut database, "put [\"bob\", 40] in customers").unwrap();
+    execute(&mut database, "alter table customers drop age").unwrap();
+    assert_eq!(rows(&mut database, "get * from customers").unwrap(),
+               vec![row!["james"], row!["jim"], row!["jimmy"], row!["bob"]]);
+}
+
+#[test]
+fn the_last_column_cant_be_dropped() {
+    let mut database = customers();
+    assert!(matches!(execute(&mut database, "alter table customers drop column age"), Err(CoilError::ColumnDoesntExist(_))));
+    execute(&mut database, "alter table customers drop column ID").unwrap();
+    assert!(matches!(execute(&mut database, "alter table customers drop column Name"), Err(CoilError::NoColumns)));
+    assert_eq!(rows(&mut database, "get * from customers").unwrap().len(), 3);
+}
+
+#[test]
+fn a_renamed_table_is_only_found_under_its_new_name() {
+    let mut database = customers();
+    execute(&mut database, "alter table customers rename clients").unwrap();
+    assert_eq!(rows(&mut database, "get Name from clients where ID = 2").unwrap(), vec![row!["jim"]]);
+    assert!(matches!(rows(&mut database, "get * from customers"), Err(CoilError::TableDoesntExist)));
+    execute(&mut database, "create table customers [x: number]").unwrap();
+    assert!(matches!(execute(&mut database, "alter table clients rename customers"), Err(CoilError::TableAlreadyExists)));
+}
+
+#[test]
+fn a_renamed_column_keeps_its_rows() {
+    let mut database = customers();
+    execute(&mut database, "alter table customers rename column Name to customer").unwrap();
+    assert_eq!(columns(&mut database, "get * from customers").unwrap(), vec!["customer", "ID"]);
+    assert_eq!(rows(&mut database, "get customer from customers where ID = 2").unwrap(), vec![row!["jim"]]);
+    assert!(matches!(rows(&mut database, "get Name from customers"), Err(CoilError::ColumnDoesntExist(_))));
+}
+
+#[test]
+fn renaming_a_column_to_its_own_name_does_nothing() {
+    let mut database = customers();
+    execute(&mut database, "alter table customers rename column Name to Name").unwrap();
+    assert_eq!(columns(&mut database, "get * from customers").unwrap(), vec!["Name", "ID"]);
+    assert_eq!(rows(&mut database, "get Name from customers where ID = 2").unwrap(), vec![row!["jim"]]);
+}
+
+#[test]
+fn renaming_a_column_to_another_columns_name_is_an_error() {
+    let mut database = customers();
+    assert!(matches!(execute(&mut database, "alter table customers rename column Name to ID"),
+                     Err(CoilError::ColumnAlreadyExists(name)) if name == "ID"));
+    assert!(matches!(execute(&mut database, "alter table customers rename column age to age"),
+                     Err(CoilError::ColumnDoesntExist(name)) if name == "age"));
+}
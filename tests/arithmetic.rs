@@ -0,0 +1,80 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn powers_bind_tighter_than_multiplication() {
+    assert_eq!(evaluate("2 * 3 ^ 2").unwrap(), FieldValue::from(18));
+    assert_eq!(evaluate("2 ** 3 * 2").unwrap(), FieldValue::from(16));
+}
+
+#[test]
+fn powers_group_from_the_right() {
+    assert_eq!(evaluate("2 ^ 3 ^ 2").unwrap(), FieldValue::from(512));
+    assert_eq!(evaluate("2 ** 3 ** 2").unwrap(), FieldValue::from(512));
+}
+
+#[test]
+fn powers_bind_tighter_than_negation() {
+    assert_eq!(evaluate("-2 ^ 2").unwrap(), FieldValue::from(-4));
+    assert_eq!(evaluate("(-2) ^ 2").unwrap(), FieldValue::from(4));
+    assert_eq!(evaluate("2 ^ -1").unwrap(), FieldValue::from(0.5));
+}
+
+#[test]
+fn powers() {
+    assert_eq!(evaluate("3 ^ 0").unwrap(), FieldValue::from(1));
+    assert_eq!(evaluate("2.0 ^ 3").unwrap(), FieldValue::from(8.0));
+    assert_eq!(evaluate("4 ^ 0.5").unwrap(), FieldValue::from(2.0));
+    assert!(matches!(evaluate("2 ^ 63"), Err(CoilError::ArithmeticOverflow)));
+}
+
+#[test]
+fn modulus() {
+    assert_eq!(evaluate("7 % 3").unwrap(), FieldValue::from(1));
+    assert_eq!(evaluate("-7 % 3").unwrap(), FieldValue::from(-1));
+    assert_eq!(evaluate("7.5 % 2").unwrap(), FieldValue::from(1.5));
+    assert!(matches!(evaluate("7 % 0"), Err(CoilError::DivisionByZero)));
+}
+
+#[test]
+fn integers_stay_integers_unless_a_float_is_involved() {
+    assert!(matches!(evaluate("7 / 2"), Ok(FieldValue::Integer(3))));
+    assert!(matches!(evaluate("7 / 2.0"), Ok(FieldValue::Float(value)) if value == 3.5));
+    assert!(matches!(evaluate("2 * 3"), Ok(FieldValue::Integer(6))));
+    assert!(matches!(evaluate("2 * 3.0"), Ok(FieldValue::Float(_))));
+    assert!(matches!(evaluate("2 ^ 3"), Ok(FieldValue::Integer(8))));
+    assert!(matches!(evaluate("2 ^ -1"), Ok(FieldValue::Float(_))));
+    assert!(matches!(evaluate("7 % 2.5"), Ok(FieldValue::Float(_))));
+    assert!(matches!(evaluate("x + none"), Ok(FieldValue::None)));
+}
+
+#[test]
+fn integer_overflow_and_dividing_by_zero_are_errors() {
+    assert!(matches!(evaluate("9223372036854775807 + x"), Err(CoilError::ArithmeticOverflow)));
+    assert!(matches!(evaluate("-9223372036854775807 - 2"), Err(CoilError::ArithmeticOverflow)));
+    assert!(matches!(evaluate("4611686018427387904 * 2"), Err(CoilError::ArithmeticOverflow)));
+    assert!(matches!(evaluate("(-9223372036854775807 - 1) / -1"), Err(CoilError::ArithmeticOverflow)));
+    for expression in ["x / 0", "x / 0.0", "x % 0.0", "1.5 / 0"] {
+        assert!(matches!(evaluate(expression), Err(CoilError::DivisionByZero)), "{}", expression);
+    }
+    assert!(matches!(evaluate("x + \"1\""), Err(CoilError::IncompatibleTypes)));
+}
+
+#[test]
+fn floats_cant_become_infinite_or_nan() {
+    for expression in ["0 ^ -1", "0.0 ^ -0.5"] {
+        assert!(matches!(evaluate(expression), Err(CoilError::DivisionByZero)), "{}", expression);
+    }
+    assert!(matches!(evaluate("10.0 ^ 400"), Err(CoilError::ArithmeticOverflow)));
+    assert!(matches!(evaluate("10.0 ^ 300 * 10.0 ^ 300"), Err(CoilError::ArithmeticOverflow)));
+    assert!(matches!(evaluate("(0 - 8) ^ 0.5"), Err(CoilError::NotANumber)));
+    assert_eq!(evaluate("0 ^ 0.5").unwrap(), FieldValue::from(0.0));
+
+    // So nothing that can't be saved ends up in a table.
+    let mut database = customers();
+    assert!(matches!(execute(&mut database, "update customers set ID = 0 ^ -1"), Err(CoilError::DivisionByZero)));
+    assert!(matches!(execute(&mut database, "put (Name = \"big\", ID = 10.0 ^ 400) in customers"), Err(CoilError::ArithmeticOverflow)));
+    assert_eq!(rows(&mut database, "get * from customers").unwrap(), vec![row!["james", 1], row!["jim", 2], row!["jimmy", 3]]);
+}
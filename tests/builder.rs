@@ -0,0 +1,58 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn an_and_of_two_equalities_runs_against_a_table() {
+    let mut database = customers();
+    execute(&mut database, "put [\"jim\", 4] in customers").unwrap();
+    let mut query = Query::new(Operation::Get);
+    query.table = Some(String::from("customers"));
+    query.condition = Some(Expression::and(Expression::eq("Name", "jim"), Expression::eq("ID", 4)));
+    let result = database.run_query(query).unwrap();
+    let rows = result.rows.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get("ID"), Some(&FieldValue::from(4)));
+}
+
+#[test]
+fn helpers_build_the_same_trees_as_the_parser() {
+    let parsed = Query::parse("get * from customers where not (ID >= 2 or Name = \"jim\")").unwrap();
+    let built = Expression::not(Expression::or(Expression::ge("ID", 2), Expression::eq("Name", "jim")));
+    assert_eq!(parsed.condition, Some(built));
+}
+
+#[test]
+fn aggregate_and_join_queries_can_be_built() {
+    let mut database = customers();
+    execute(&mut database, "create table orders [cust_id: number, total: number]").unwrap();
+    execute(&mut database, "put [1, 10] in orders").unwrap();
+    execute(&mut database, "put [1, 5] in orders").unwrap();
+    execute(&mut database, "put [3, 7] in orders").unwrap();
+
+    let mut query = Query::new(Operation::Get);
+    query.table = Some(String::from("orders"));
+    query.aggregates = vec![Aggregate{function: AggregateFunction::Sum, column: Some(String::from("total"))}];
+    let result = database.run_query(query).unwrap();
+    assert_eq!(result.rows.unwrap()[0].get("sum(total)"), Some(&FieldValue::from(22)));
+
+    let mut query = Query::new(Operation::Get);
+    query.table = Some(String::from("customers"));
+    query.join = Some(Join{table: String::from("orders"),
+                           condition: Expression::binary(ExpressionType::Equal,
+                                                         Expression::column("customers.ID"),
+                                                         Expression::column("orders.cust_id"))});
+    query.condition = Some(Expression::gt("orders.total", 6));
+    assert_eq!(database.run_query(query).unwrap().rows.unwrap().len(), 2);
+}
+
+#[test]
+fn alter_queries_can_be_built() {
+    let mut database = customers();
+    let mut query = Query::new(Operation::Alter);
+    query.table = Some(String::from("customers"));
+    query.alteration = Some(Alteration::RenameColumn(String::from("Name"), String::from("FullName")));
+    database.run_query(query).unwrap();
+    assert_eq!(columns(&mut database, "get * from customers").unwrap(), ["FullName", "ID"]);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn open_save_and_load() {
+    let directory = directory("commands");
+    let path = directory.join("shop");
+    let mut database = customers();
+
+    let message = run_command(&mut database, &format!(".open {}", path.display())).unwrap();
+    assert_eq!(message, format!("Created `shop` at {}", path.display()));
+    assert_eq!(database.table_count(), 0);
+    execute(&mut database, "create table items [Name: text]").unwrap();
+    execute(&mut database, "put [\"pen\"] in items").unwrap();
+    run_command(&mut database, ".save").unwrap();
+
+    let mut other = customers();
+    let message = run_command(&mut other, &format!(".load {}", path.display())).unwrap();
+    assert_eq!(message, format!("Loaded `shop` from {}", path.display()));
+    assert_eq!(rows(&mut other, "get * from items").unwrap(), vec![row!["pen"]]);
+
+    let mut other = customers();
+    run_command(&mut other, &format!(".open {}", path.display())).unwrap();
+    assert_eq!(other.table_names(), ["items"]);
+}
+
+#[test]
+fn a_loaded_database_saves_back_where_it_came_from() {
+    let directory = directory("commands-move");
+    let database = Database::new(String::from("original"), DatabaseConfig::new(directory.clone())).unwrap();
+    database.save().unwrap();
+    std::fs::rename(directory.join("original"), directory.join("moved")).unwrap();
+
+    let mut loaded = customers();
+    run_command(&mut loaded, &format!(".load {}", directory.join("moved").display())).unwrap();
+    assert_eq!(loaded.name(), "moved");
+    assert_eq!(loaded.file_path(), directory.join("moved"));
+}
+
+#[test]
+fn bad_commands_are_errors() {
+    let mut database = customers();
+    for command in [".save now", ".load", ".open", ".schema x", ".quit", ".load a b"] {
+        assert!(matches!(run_command(&mut database, command), Err(CoilError::InvalidCommand(_))), "{}", command);
+    }
+    assert!(matches!(run_command(&mut database, ".load /nonexistent-coil-directory/shop"), Err(CoilError::DatabaseDoesntExist)));
+    assert!(matches!(run_command(&mut database, ".save"), Err(CoilError::SaveFailed(_))));
+    assert_eq!(database.name(), "test");
+}
+
+#[test]
+fn schema_prints_the_create_queries() {
+    let mut database = customers();
+    execute(&mut database, "create table orders [ID: number primary auto]").unwrap();
+    assert_eq!(run_command(&mut database, ".schema").unwrap(),
+               "create table customers [Name: text, ID: number];\ncreate table orders [ID: number primary auto];");
+    assert_eq!(run_command(&mut Database::in_memory(String::from("empty")).unwrap(), ".schema").unwrap(), "");
+}
@@ -0,0 +1,60 @@
+#![allow(dead_code)]
+
+use coil::*;
+
+// The values of a row, in column order, e.g. `row!["jim", 2]`.
+#[macro_export]
+macro_rules! row {
+    ($($value:expr),* $(,)?) => {
+        vec![$(FieldValue::from($value)),*]
+    };
+}
+
+// An in-memory database holding the REPL's
+// `customers` table.
+pub fn customers() -> Database {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table customers [Name: text, ID: number]").unwrap();
+    execute(&mut database, "put [\"james\", 1] in customers").unwrap();
+    execute(&mut database, "put [\"jim\", 2] in customers").unwrap();
+    execute(&mut database, "put [\"jimmy\", 3] in customers").unwrap();
+    database
+}
+
+// Runs a query, returning how many rows it changed.
+pub fn execute(database: &mut Database, query: &str) -> Result<Option<usize>, CoilError> {
+    database.run_query(Query::parse(query)?).map(|result| result.affected)
+}
+
+// Runs a query, returning the values of the rows
+// it gets, in column order.
+pub fn rows(database: &mut Database, query: &str) -> Result<Vec<Vec<FieldValue>>, CoilError> {
+    let result = database.run_query(Query::parse(query)?)?;
+    let columns = result.columns.unwrap_or_default();
+    Ok(result.rows.unwrap_or_default().iter().map(|row| {
+        columns.iter().map(|column| row.get(column).cloned().unwrap_or(FieldValue::None)).collect()
+    }).collect())
+}
+
+// Runs a query, returning the names of its columns.
+pub fn columns(database: &mut Database, query: &str) -> Result<Vec<String>, CoilError> {
+    Ok(database.run_query(Query::parse(query)?)?.columns.unwrap_or_default())
+}
+
+// A fresh directory for a test to save databases
+// in, named after the test.
+pub fn directory(test: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("coil-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+// Evaluates an expression against a row whose
+// only column, `x`, holds 1.
+pub fn evaluate(expression: &str) -> Result<FieldValue, CoilError> {
+    let query = Query::parse(&format!("get * from t where {}", expression))?;
+    let mut table = Table::new(String::from("t"), vec![Column::new(String::from("x"), FieldType::Number)]);
+    table.new_row(vec![FieldValue::from(1)])?;
+    table.all_rows()[0].evaluate(query.condition.as_ref().unwrap())
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+fn ids(condition: &str) -> Vec<Vec<FieldValue>> {
+    let mut database = customers();
+    rows(&mut database, &format!("get ID from customers where {}", condition)).unwrap()
+}
+
+#[test]
+fn conditions_nest() {
+    assert_eq!(ids("(ID = 1 or ID = 3) and (Name = \"jimmy\" or Name = \"jim\")"), vec![row![3]]);
+    assert_eq!(ids("ID = 2 or (Name != \"jim\" and (ID = 1 or ID > 5))"), vec![row![1], row![2]]);
+    assert_eq!(ids("((ID >= 2 and ID <= 3) or (Name = \"james\" and ID = 9)) and Name != \"jimmy\""), vec![row![2]]);
+}
+
+#[test]
+fn and_or_and_xor_follow_their_truth_tables() {
+    for (l, r) in [(false, false), (false, true), (true, false), (true, true)] {
+        assert_eq!(evaluate(&format!("{} and {}", l, r)).unwrap(), FieldValue::from(l && r));
+        assert_eq!(evaluate(&format!("{} or {}", l, r)).unwrap(), FieldValue::from(l || r));
+        assert_eq!(evaluate(&format!("{} xor {}", l, r)).unwrap(), FieldValue::from(l != r));
+    }
+}
+
+#[test]
+fn or_and_and_skip_their_right_side_once_the_left_decides() {
+    // `y` doesn't exist, so checking it would be an error.
+    assert_eq!(evaluate("x = 1 or y = 1").unwrap(), FieldValue::from(true));
+    assert_eq!(evaluate("x = 2 and y = 1").unwrap(), FieldValue::from(false));
+    assert!(matches!(evaluate("x = 2 or y = 1"), Err(CoilError::ColumnDoesntExist(_))));
+    assert!(matches!(evaluate("x = 1 xor y = 1"), Err(CoilError::ColumnDoesntExist(_))));
+}
+
+#[test]
+fn the_operands_of_and_have_to_be_conditions() {
+    assert!(evaluate("x and true").is_err());
+}
+
+#[test]
+fn conditions_can_do_arithmetic() {
+    assert_eq!(ids("ID * 2 = 4"), vec![row![2]]);
+    assert_eq!(ids("ID + 1 > 3 - 1"), vec![row![2], row![3]]);
+    assert_eq!(ids("ID / 2 = 1"), vec![row![2], row![3]]);
+    assert_eq!(ids("ID / 2.0 = 0.5"), vec![row![1]]);
+    assert_eq!(evaluate("x + \"a\" = 1").unwrap_err().to_string(), "incompatible types");
+}
+
+#[test]
+fn in_tests_membership_of_a_list() {
+    assert_eq!(ids("ID in [1, 3]"), vec![row![1], row![3]]);
+    assert_eq!(ids("ID in [2.0, 7]"), vec![row![2]]);
+    assert_eq!(ids("Name in [\"jim\", \"bob\"]"), vec![row![2]]);
+    assert_eq!(ids("not ID in [1, 3]"), vec![row![2]]);
+    assert_eq!(ids("ID in []"), Vec::<Vec<FieldValue>>::new());
+    assert_eq!(ids("not ID in []").len(), 3);
+}
+
+#[test]
+fn in_lists_have_to_match_the_values_type() {
+    let mut database = customers();
+    assert!(matches!(rows(&mut database, "get * from customers where ID in [1, \"2\"]"), Err(CoilError::IncompatibleTypes)));
+    assert!(matches!(rows(&mut database, "get * from customers where Name in [1]"), Err(CoilError::IncompatibleTypes)));
+}
+
+#[test]
+fn between_includes_both_bounds() {
+    assert_eq!(ids("ID between 1 and 2"), vec![row![1], row![2]]);
+    assert_eq!(ids("ID between 2 and 2"), vec![row![2]]);
+    assert_eq!(ids("ID between 1.5 and 2.5"), vec![row![2]]);
+    assert_eq!(ids("ID between 4 and 9"), Vec::<Vec<FieldValue>>::new());
+    assert_eq!(ids("ID between 3 and 1"), Vec::<Vec<FieldValue>>::new());
+    assert_eq!(ids("Name between \"jim\" and \"jimmy\""), vec![row![2], row![3]]);
+    assert_eq!(ids("ID between 0 + 1 and 1 * 2 and Name = \"jim\""), vec![row![2]]);
+}
+
+#[test]
+fn between_bounds_have_to_match_the_values_type() {
+    let mut database = customers();
+    for query in ["get * from customers where ID between \"a\" and \"z\"",
+                  "get * from customers where ID between 1 and \"z\"",
+                  "get * from customers where Name between 1 and 2"] {
+        assert!(matches!(rows(&mut database, query), Err(CoilError::IncompatibleTypes)), "{}", query);
+    }
+}
+
+#[test]
+fn is_none_and_is_not_none_check_for_missing_values() {
+    let mut database = customers();
+    execute(&mut database, "put [none, 4] in customers").unwrap();
+    assert_eq!(rows(&mut database, "get ID from customers where Name is none").unwrap(), vec![row![4]]);
+    assert_eq!(rows(&mut database, "get ID from customers where Name is not none").unwrap(), vec![row![1], row![2], row![3]]);
+    assert_eq!(rows(&mut database, "get ID from customers where ID is none").unwrap(), Vec::<Vec<FieldValue>>::new());
+}
+
+#[test]
+fn ieq_compares_text_without_regard_to_case() {
+    assert_eq!(ids("Name ieq \"JIM\""), vec![row![2]]);
+    assert_eq!(ids("Name ieq \"jImMy\""), vec![row![3]]);
+    assert_eq!(ids("Name = \"JIM\""), Vec::<Vec<FieldValue>>::new());
+    // Numbers compare like they do with `=`.
+    assert_eq!(ids("ID ieq 2"), vec![row![2]]);
+    assert_eq!(ids("ID ieq 2.0"), vec![row![2]]);
+    assert!(matches!(evaluate("x ieq \"1\""), Err(CoilError::IncompatibleTypes)));
+}
+
+#[test]
+fn xor_is_true_when_exactly_one_side_is() {
+    assert_eq!(ids("ID = 1 xor Name = \"jim\""), vec![row![1], row![2]]);
+    assert_eq!(ids("ID < 3 xor Name like \"jim%\""), vec![row![1], row![3]]);
+    assert_eq!(ids("ID > 0 xor ID > 0"), Vec::<Vec<FieldValue>>::new());
+}
+
+#[test]
+fn not_negates_conditions() {
+    assert_eq!(ids("not ID = 2"), vec![row![1], row![3]]);
+    assert_eq!(ids("!(ID = 2)"), vec![row![1], row![3]]);
+    assert_eq!(ids("not not ID = 2"), vec![row![2]]);
+    assert_eq!(ids("not (ID = 1 or (Name = \"jim\" and not ID = 3))"), vec![row![3]]);
+    assert_eq!(ids("not ID = 1 and not ID = 3"), vec![row![2]]);
+    assert!(matches!(evaluate("not x"), Err(CoilError::NonBooleanCondition)));
+}
+
+#[test]
+fn text_and_numbers_cant_be_compared() {
+    for condition in ["x = \"1\"", "x != \"1\"", "x < \"a\"", "\"1\" >= x", "true = 1"] {
+        assert!(matches!(evaluate(condition), Err(CoilError::IncompatibleTypes)), "{}", condition);
+    }
+    // `none` can be compared with anything.
+    assert_eq!(evaluate("x = none").unwrap(), FieldValue::from(false));
+    assert_eq!(evaluate("\"a\" != none").unwrap(), FieldValue::from(true));
+}
+
+#[test]
+fn integers_and_floats_compare_by_value() {
+    assert_eq!(FieldValue::Integer(5), FieldValue::Float(5.0));
+    assert_eq!(evaluate("x = 1.0").unwrap(), FieldValue::from(true));
+    assert_eq!(evaluate("x != 1.0").unwrap(), FieldValue::from(false));
+    assert_eq!(evaluate("x < 1.5").unwrap(), FieldValue::from(true));
+    assert_eq!(evaluate("x >= 0.999").unwrap(), FieldValue::from(true));
+    assert_eq!(evaluate("x = 1.000001").unwrap(), FieldValue::from(false));
+    assert_eq!(ids("ID = 3.0 or ID < 1.5"), vec![row![1], row![3]]);
+}
+
+#[test]
+fn arithmetic_on_its_own_isnt_a_condition() {
+    let mut database = customers();
+    for condition in ["1 + 1", "ID * 2", "-ID", "+ID", "ID", "\"jim\""] {
+        let result = rows(&mut database, &format!("get * from customers where {}", condition));
+        assert!(matches!(result, Err(CoilError::NonBooleanCondition)), "{}: {:?}", condition, result);
+    }
+    assert!(matches!(execute(&mut database, "delete from customers where ID + 1"), Err(CoilError::NonBooleanCondition)));
+    assert_eq!(rows(&mut database, "get count(*) from customers").unwrap(), vec![row![3]]);
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+fn users(columns: &str) -> Database {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, &format!("create table users [{}]", columns)).unwrap();
+    database
+}
+
+#[test]
+fn primary_keys_are_unique_and_never_none() {
+    let mut database = users("ID: number primary, Name: text");
+    execute(&mut database, "put [1, \"jim\"] in users").unwrap();
+    assert!(matches!(execute(&mut database, "put [1, \"bob\"] in users"), Err(CoilError::DuplicateKey)));
+    assert!(matches!(execute(&mut database, "put [1.0, \"bob\"] in users"), Err(CoilError::DuplicateKey)));
+    assert!(matches!(execute(&mut database, "put [none, \"bob\"] in users"),
+                     Err(CoilError::NullConstraintViolation(column)) if column == "ID"));
+    assert_eq!(rows(&mut database, "get * from users").unwrap(), vec![row![1, "jim"]]);
+    assert!(matches!(execute(&mut database, "update users set ID = 1"), Ok(Some(1))));
+}
+
+#[test]
+fn a_table_can_only_have_one_primary_key() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    assert!(matches!(execute(&mut database, "create table users [ID: number primary, Name: text primary]"),
+                     Err(CoilError::MultiplePrimaryKeys)));
+}
+
+#[test]
+fn a_table_cant_have_two_columns_with_the_same_name() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    for columns in ["a: number, a: text", "a: number, b: text, a: number"] {
+        assert!(matches!(execute(&mut database, &format!("create table users [{}]", columns)),
+                         Err(CoilError::ColumnAlreadyExists(column)) if column == "a"));
+    }
+    assert!(database.get_table(String::from("users")).is_none());
+    // Names are compared exactly, so these are different.
+    execute(&mut database, "create table users [a: number, A: text]").unwrap();
+}
+
+#[test]
+fn unique_columns_reject_duplicates_but_allow_nones() {
+    let mut database = users("Name: text, email: text unique");
+    execute(&mut database, "put [\"jim\", \"jim@example.com\"] in users").unwrap();
+    assert!(matches!(execute(&mut database, "put [\"bob\", \"jim@example.com\"] in users"),
+                     Err(CoilError::UniqueViolation(column)) if column == "email"));
+    execute(&mut database, "put [\"bob\", none] in users").unwrap();
+    execute(&mut database, "put [\"tim\", none] in users").unwrap();
+    assert!(matches!(execute(&mut database, "update users set email = \"jim@example.com\" where Name = \"bob\""),
+                     Err(CoilError::UniqueViolation(_))));
+    assert_eq!(rows(&mut database, "get Name from users").unwrap(), vec![row!["jim"], row!["bob"], row!["tim"]]);
+}
+
+#[test]
+fn not_null_columns_reject_none() {
+    let mut database = users("Name: text notnull, nickname: text");
+    assert!(matches!(execute(&mut database, "put [none, \"jj\"] in users"),
+                     Err(CoilError::NullConstraintViolation(column)) if column == "Name"));
+    execute(&mut database, "put [\"jim\", none] in users").unwrap();
+    assert!(matches!(execute(&mut database, "update users set Name = none"),
+                     Err(CoilError::NullConstraintViolation(_))));
+    assert_eq!(rows(&mut database, "get * from users").unwrap(), vec![row!["jim", FieldValue::None]]);
+}
+
+#[test]
+fn left_out_values_take_their_defaults() {
+    let mut database = users("Name: text, status: text default \"new\", score: number default 0");
+    execute(&mut database, "put [\"jim\"] in users").unwrap();
+    execute(&mut database, "put [\"bob\", \"old\"] in users").unwrap();
+    execute(&mut database, "put [\"tim\", \"old\", 5] in users").unwrap();
+    assert_eq!(rows(&mut database, "get * from users").unwrap(),
+               vec![row!["jim", "new", 0], row!["bob", "old", 0], row!["tim", "old", 5]]);
+}
+
+#[test]
+fn values_without_defaults_cant_be_left_out() {
+    let mut database = users("Name: text, status: text default \"new\", score: number");
+    assert!(matches!(execute(&mut database, "put [\"jim\"] in users"), Err(CoilError::NotEnoughValues)));
+    assert!(matches!(execute(&mut database, "put [\"jim\", \"new\", 1, 2] in users"), Err(CoilError::TooManyValues)));
+}
+
+#[test]
+fn defaults_have_to_match_their_columns_type() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    assert!(matches!(execute(&mut database, "create table users [score: number default \"none\"]"),
+                     Err(CoilError::MismatchedTypes{..})));
+}
+
+#[test]
+fn auto_columns_count_past_explicit_values() {
+    let mut database = users("ID: number primary auto, Name: text");
+    execute(&mut database, "put [\"a\"] in users").unwrap();
+    execute(&mut database, "put [\"b\"] in users").unwrap();
+    execute(&mut database, "put [10, \"c\"] in users").unwrap();
+    execute(&mut database, "put [\"d\"] in users").unwrap();
+    execute(&mut database, "put [5, \"e\"] in users").unwrap();
+    execute(&mut database, "put [\"f\"] in users").unwrap();
+    assert!(matches!(execute(&mut database, "put [2, \"g\"] in users"), Err(CoilError::DuplicateKey)));
+    assert_eq!(rows(&mut database, "get ID from users").unwrap(),
+               vec![row![1], row![2], row![10], row![11], row![5], row![12]]);
+}
+
+#[test]
+fn only_number_columns_can_be_auto() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    assert!(execute(&mut database, "create table users [Name: text auto]").is_err());
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn csv_files_are_imported_by_column_name() {
+    let directory = directory("csv-import");
+    let path = directory.join("customers.csv");
+    std::fs::write(&path, "ID,Name\n4,bob\n5,\n6,\"smith, jo\"\n").unwrap();
+    let mut database = customers();
+    assert_eq!(execute(&mut database, &format!("put csv \"{}\" in customers", path.display())).unwrap(), Some(3));
+    assert_eq!(rows(&mut database, "get * from customers where ID > 3").unwrap(),
+               vec![row!["bob", 4], row![FieldValue::None, 5], row!["smith, jo", 6]]);
+}
+
+#[test]
+fn a_bad_csv_file_imports_nothing() {
+    let directory = directory("csv-bad-import");
+    let path = directory.join("customers.csv");
+    std::fs::write(&path, "ID,Name\n4,bob\nfive,tim\n").unwrap();
+    let mut database = customers();
+    match execute(&mut database, &format!("put csv \"{}\" in customers", path.display())) {
+        Err(CoilError::ImportFailed(message)) => { assert!(message.starts_with("line 3:"), "{}", message); },
+        other => { panic!("{:?}", other); }
+    }
+    assert!(matches!(execute(&mut database, &format!("put csv \"{}\" in customers", directory.join("missing.csv").display())),
+                     Err(CoilError::ImportFailed(_))));
+    assert_eq!(rows(&mut database, "get * from customers").unwrap().len(), 3);
+}
+
+#[test]
+fn results_export_to_csv() {
+    let mut database = customers();
+    execute(&mut database, "put [\"smith, \\\"jo\\\"\", 4.5] in customers").unwrap();
+    execute(&mut database, "put [none, 5.0] in customers").unwrap();
+    let result = database.run_query(Query::parse("get * from customers where ID > 2").unwrap()).unwrap();
+    assert_eq!(result.to_csv(), "Name,ID\njimmy,3\n\"smith, \"\"jo\"\"\",4.5\n,5\n");
+}
+
+#[test]
+fn only_results_with_rows_export_to_csv() {
+    let mut database = customers();
+    let result = database.run_query(Query::parse("get * from customers where ID > 5").unwrap()).unwrap();
+    assert_eq!(result.to_csv(), "Name,ID\n");
+    let result = database.run_query(Query::parse("delete from customers where ID = 1").unwrap()).unwrap();
+    assert_eq!(result.to_csv(), "");
+}
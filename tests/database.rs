@@ -0,0 +1,136 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn dangerous_names_are_rejected() {
+    for name in ["", ".", "..", "../evil", "a/b", "a\\b", "a\0b"] {
+        assert!(matches!(Database::new(String::from(name), DatabaseConfig::default()), Err(CoilError::InvalidName(_))), "{:?}", name);
+        assert!(matches!(Database::in_memory(String::from(name)), Err(CoilError::InvalidName(_))), "{:?}", name);
+    }
+}
+
+#[test]
+fn databases_can_be_renamed() {
+    let mut database = customers();
+    database.rename(String::from("shop")).unwrap();
+    assert_eq!(database.name(), "shop");
+    assert!(matches!(database.rename(String::from("../evil")), Err(CoilError::InvalidName(_))));
+    assert_eq!(database.name(), "shop");
+}
+
+#[test]
+fn loading_a_file_with_a_dangerous_name_fails() {
+    let directory = directory("dangerous-name");
+    let database = Database::new(String::from("safe"), DatabaseConfig::new(directory.clone())).unwrap();
+    database.save().unwrap();
+    let path = directory.join("safe");
+    let contents = std::fs::read_to_string(&path).unwrap().replacen("\"safe\"", "\"../evil\"", 1);
+    std::fs::write(&path, contents).unwrap();
+    assert!(matches!(Database::from_file(&path), Err(CoilError::InvalidName(_))));
+}
+
+#[test]
+fn opening_a_path_without_a_file_name_fails() {
+    let mut database = customers();
+    assert!(matches!(run_command(&mut database, ".open /nonexistent-coil-directory/.."), Err(CoilError::InvalidName(_))));
+    assert_eq!(database.name(), "test");
+}
+
+#[test]
+fn tables_can_be_deleted() {
+    let mut database = customers();
+    execute(&mut database, "create table orders [ID: number]").unwrap();
+    database.delete_table("orders").unwrap();
+    assert_eq!(database.table_names(), ["customers"]);
+    assert!(matches!(database.delete_table("orders"), Err(CoilError::TableDoesntExist)));
+
+    execute(&mut database, "delete table customers").unwrap();
+    assert_eq!(database.table_count(), 0);
+    assert!(matches!(rows(&mut database, "get * from customers"), Err(CoilError::TableDoesntExist)));
+    assert!(matches!(execute(&mut database, "delete table customers"), Err(CoilError::TableDoesntExist)));
+}
+
+#[test]
+fn databases_can_only_be_deleted_from_a_workspace() {
+    let mut database = customers();
+    assert!(matches!(execute(&mut database, "delete database test"), Err(CoilError::NeedsWorkspace)));
+}
+
+#[test]
+fn tables_can_be_listed_and_fetched() {
+    let mut database = customers();
+    execute(&mut database, "create table orders [ID: number]").unwrap();
+    execute(&mut database, "create table items [Name: text]").unwrap();
+    assert_eq!(database.table_count(), 3);
+    assert_eq!(database.table_names(), ["customers", "orders", "items"]);
+    assert_eq!(database.tables().map(|table| table.row_count()).collect::<Vec<usize>>(), [3, 0, 0]);
+    assert_eq!(database.get_table(String::from("orders")).unwrap().name(), "orders");
+    assert!(database.get_table(String::from("suppliers")).is_none());
+}
+
+#[test]
+fn tables_need_at_least_one_column() {
+    let mut database = customers();
+    assert!(matches!(database.new_table(String::from("empty"), Vec::new()), Err(CoilError::NoColumns)));
+    assert!(execute(&mut database, "create table empty []").is_err());
+    assert_eq!(database.table_names(), ["customers"]);
+}
+
+// Another database with a `customers` table created
+// by `create`.
+fn shard(create: &str) -> Database {
+    let mut database = Database::in_memory(String::from("shard")).unwrap();
+    execute(&mut database, create).unwrap();
+    execute(&mut database, "put [\"bob\", 4] in customers").unwrap();
+    database
+}
+
+#[test]
+fn merging_adds_the_other_databases_tables() {
+    let mut database = customers();
+    let mut other = Database::in_memory(String::from("other")).unwrap();
+    execute(&mut other, "create table orders [ID: number, total: number]").unwrap();
+    execute(&mut other, "put [1, 20] in orders").unwrap();
+    database.merge(other, MergePolicy::Error).unwrap();
+    assert_eq!(rows(&mut database, "show tables").unwrap(), vec![row!["customers"], row!["orders"]]);
+    assert_eq!(rows(&mut database, "get * from orders").unwrap(), vec![row![1, 20]]);
+    assert_eq!(rows(&mut database, "get count(*) from customers").unwrap(), vec![row![3]]);
+}
+
+#[test]
+fn merging_a_table_thats_already_there_is_an_error() {
+    let mut database = customers();
+    let other = shard("create table customers [Name: text, ID: number]");
+    assert!(matches!(database.merge(other, MergePolicy::Error), Err(CoilError::TableAlreadyExists)));
+    // Appending needs the columns to be the same.
+    let other = shard("create table customers [Name: text, ID: number primary]");
+    assert!(matches!(database.merge(other, MergePolicy::Append), Err(CoilError::TableAlreadyExists)));
+    assert_eq!(rows(&mut database, "get count(*) from customers").unwrap(), vec![row![3]]);
+}
+
+#[test]
+fn merging_can_append_rows_to_a_table_with_the_same_columns() {
+    let mut database = customers();
+    database.merge(shard("create table customers [Name: text, ID: number]"), MergePolicy::Append).unwrap();
+    assert_eq!(rows(&mut database, "get * from customers").unwrap(),
+               vec![row!["james", 1], row!["jim", 2], row!["jimmy", 3], row!["bob", 4]]);
+}
+
+#[test]
+fn a_merge_that_clashes_with_a_key_changes_nothing() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table customers [Name: text, ID: number primary]").unwrap();
+    execute(&mut database, "put [\"bob\", 4] in customers").unwrap();
+    // Only the last row clashes, after the new table
+    // and the first row have gone in.
+    let mut other = Database::in_memory(String::from("other")).unwrap();
+    execute(&mut other, "create table orders [ID: number]").unwrap();
+    execute(&mut other, "create table customers [Name: text, ID: number primary]").unwrap();
+    execute(&mut other, "put [\"jim\", 2] in customers").unwrap();
+    execute(&mut other, "put [\"bob\", 4] in customers").unwrap();
+    assert!(matches!(database.merge(other, MergePolicy::Append), Err(CoilError::DuplicateKey)));
+    assert_eq!(rows(&mut database, "get * from customers").unwrap(), vec![row!["bob", 4]]);
+    assert!(database.get_table(String::from("orders")).is_none());
+}
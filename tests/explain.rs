@@ -0,0 +1,42 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+// The steps `explain` gives for `query`, leaving out
+// the parsed query that follows them.
+fn steps(database: &mut Database, query: &str) -> Vec<String> {
+    let result = database.run_query(Query::parse(&format!("explain {}", query)).unwrap()).unwrap();
+    let explanation = result.explanation.clone().unwrap();
+    explanation.split("\n\n").next().unwrap().lines().map(String::from).collect()
+}
+
+fn indexed() -> Database {
+    let mut database = customers();
+    database.get_table_mut(String::from("customers")).unwrap().create_index("ID").unwrap();
+    database
+}
+
+#[test]
+fn explain_reports_index_lookups_for_indexed_equalities() {
+    let mut database = indexed();
+    assert_eq!(steps(&mut database, "get * from customers where ID = 2"), ["index lookup on `ID` in `customers`"]);
+    assert_eq!(steps(&mut database, "delete from customers where ID = 2 and Name = \"jim\""),
+               ["index lookup on `ID` in `customers`", "remove the matching rows"]);
+}
+
+#[test]
+fn explain_reports_scans_for_ranges() {
+    let mut database = indexed();
+    assert_eq!(steps(&mut database, "get * from customers where ID > 2"), ["full scan of `customers`"]);
+    assert_eq!(steps(&mut database, "get * from customers where Name = \"jim\" order by ID limit 1"),
+               ["full scan of `customers`", "sort by `ID` ascending", "keep at most 1 rows"]);
+}
+
+#[test]
+fn explain_doesnt_run_the_query() {
+    let mut database = customers();
+    steps(&mut database, "delete from customers");
+    assert_eq!(rows(&mut database, "get * from customers").unwrap().len(), 3);
+    assert!(matches!(execute(&mut database, "explain get * from suppliers"), Err(CoilError::TableDoesntExist)));
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+// `customers` plus an `orders` table, where jim
+// has two orders, james one and jimmy none.
+fn shop() -> Database {
+    let mut database = customers();
+    execute(&mut database, "create table orders [cust_id: number, total: number]").unwrap();
+    execute(&mut database, "put [2, 10] in orders").unwrap();
+    execute(&mut database, "put [1, 5] in orders").unwrap();
+    execute(&mut database, "put [2, 7] in orders").unwrap();
+    execute(&mut database, "put [9, 1] in orders").unwrap();
+    database
+}
+
+#[test]
+fn joined_rows_are_the_pairs_that_match() {
+    let mut database = shop();
+    let query = "get customers.Name, orders.total from customers join orders on customers.ID = orders.cust_id";
+    assert_eq!(columns(&mut database, query).unwrap(), vec!["customers.Name", "orders.total"]);
+    // Many orders to one customer, and none
+    // for the customer or order without a match.
+    assert_eq!(rows(&mut database, query).unwrap(), vec![row!["james", 5], row!["jim", 10], row!["jim", 7]]);
+}
+
+#[test]
+fn joins_without_matches_are_empty() {
+    let mut database = shop();
+    let query = "get * from customers join orders on customers.ID = orders.cust_id + 10";
+    assert_eq!(rows(&mut database, query).unwrap(), Vec::<Vec<FieldValue>>::new());
+}
+
+#[test]
+fn joined_rows_can_be_filtered() {
+    let mut database = shop();
+    let query = "get orders.total from customers join orders on customers.ID = orders.cust_id where customers.Name = \"jim\" and orders.total > 8";
+    assert_eq!(rows(&mut database, query).unwrap(), vec![row![10]]);
+}
+
+#[test]
+fn a_table_cant_be_joined_with_itself() {
+    let mut database = shop();
+    assert!(matches!(rows(&mut database, "get * from orders join orders on orders.cust_id = orders.cust_id"),
+                     Err(CoilError::SelfJoin(table)) if table == "orders"));
+}
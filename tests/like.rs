@@ -0,0 +1,50 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+fn names(pattern: &str) -> Vec<Vec<FieldValue>> {
+    let mut database = customers();
+    execute(&mut database, "put [\"50% off\", 4] in customers").unwrap();
+    execute(&mut database, "put [\"500 off\", 5] in customers").unwrap();
+    rows(&mut database, &format!("get Name from customers where Name like {}", pattern)).unwrap()
+}
+
+#[test]
+fn percent_matches_any_run_of_characters() {
+    assert_eq!(names("\"jim%\""), vec![row!["jim"], row!["jimmy"]]);
+    assert_eq!(names("\"%s\""), vec![row!["james"]]);
+    assert_eq!(names("\"%m%\""), vec![row!["james"], row!["jim"], row!["jimmy"]]);
+}
+
+#[test]
+fn underscore_matches_exactly_one_character() {
+    assert_eq!(names("\"j_m\""), vec![row!["jim"]]);
+    assert_eq!(names("\"ji_\""), vec![row!["jim"]]);
+}
+
+#[test]
+fn a_pattern_without_wildcards_matches_exactly() {
+    assert_eq!(names("\"jim\""), vec![row!["jim"]]);
+    assert_eq!(names("\"Jim\""), Vec::<Vec<FieldValue>>::new());
+}
+
+#[test]
+fn a_backslash_makes_a_wildcard_literal() {
+    assert_eq!(names("\"50%\""), vec![row!["50% off"], row!["500 off"]]);
+    assert_eq!(names("\"50\\%%\""), vec![row!["50% off"]]);
+}
+
+#[test]
+fn like_on_a_number_is_mismatched_types() {
+    let mut database = customers();
+    match rows(&mut database, "get * from customers where ID like \"1%\"") {
+        Err(CoilError::MismatchedTypes{column, expected: FieldType::Text, got}) => {
+            assert_eq!(column, "ID");
+            assert_eq!(got, FieldValue::from(1));
+        },
+        other => { panic!("{:?}", other); }
+    }
+    assert!(matches!(rows(&mut database, "get * from customers where Name like 1"),
+                     Err(CoilError::MismatchedTypes{expected: FieldType::Text, ..})));
+}
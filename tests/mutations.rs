@@ -0,0 +1,169 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn changes_report_how_many_rows_they_affected() {
+    let mut database = customers();
+    assert_eq!(execute(&mut database, "put [\"bob\", 4] in customers").unwrap(), Some(1));
+    assert_eq!(execute(&mut database, "update customers set ID = ID + 10 where ID > 2").unwrap(), Some(2));
+    assert_eq!(execute(&mut database, "delete from customers where ID > 10").unwrap(), Some(2));
+    assert_eq!(execute(&mut database, "delete from customers where ID > 10").unwrap(), Some(0));
+    assert_eq!(execute(&mut database, "get * from customers").unwrap(), None);
+}
+
+#[test]
+fn the_affected_count_is_shown() {
+    let mut database = customers();
+    let result = database.run_query(Query::parse("delete from customers where ID < 3").unwrap()).unwrap();
+    assert_eq!(result.to_table_string(), "2 rows affected\n");
+    let result = database.run_query(Query::parse("put [\"bob\", 4] in customers").unwrap()).unwrap();
+    assert_eq!(result.to_table_string(), "1 row affected\n");
+}
+
+fn keyed() -> Database {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table users [ID: number primary, email: text unique, Name: text]").unwrap();
+    execute(&mut database, "put [1, \"a@x\", \"jim\"] in users").unwrap();
+    execute(&mut database, "put [2, \"b@x\", \"bob\"] in users").unwrap();
+    database
+}
+
+#[test]
+fn upserts_insert_rows_without_a_conflict() {
+    let mut database = keyed();
+    execute(&mut database, "put [3, \"c@x\", \"tim\"] in users on conflict update").unwrap();
+    assert_eq!(rows(&mut database, "get ID from users").unwrap(), vec![row![1], row![2], row![3]]);
+}
+
+#[test]
+fn upserts_overwrite_the_conflicting_row() {
+    let mut database = keyed();
+    execute(&mut database, "put [1, \"a@y\", \"jimmy\"] in users on conflict update").unwrap();
+    execute(&mut database, "put [5, \"b@x\", \"rob\"] in users on conflict update").unwrap();
+    assert_eq!(rows(&mut database, "get * from users").unwrap(), vec![row![1, "a@y", "jimmy"], row![5, "b@x", "rob"]]);
+    assert_eq!(rows(&mut database, "get Name from users where ID = 5").unwrap(), vec![row!["rob"]]);
+}
+
+#[test]
+fn upserts_that_conflict_with_two_rows_are_an_error() {
+    let mut database = keyed();
+    assert!(execute(&mut database, "put [1, \"b@x\", \"x\"] in users on conflict update").is_err());
+    assert_eq!(rows(&mut database, "get * from users").unwrap(), vec![row![1, "a@x", "jim"], row![2, "b@x", "bob"]]);
+    assert!(matches!(execute(&mut database, "put [1, \"q@x\", \"x\"] in users"), Err(CoilError::DuplicateKey)));
+}
+
+#[test]
+fn deletes_remove_the_rows_that_match() {
+    let mut database = customers();
+    assert_eq!(execute(&mut database, "delete from customers where Name like \"jim%\"").unwrap(), Some(2));
+    assert_eq!(rows(&mut database, "get * from customers").unwrap(), vec![row!["james", 1]]);
+}
+
+#[test]
+fn deletes_that_match_nothing_remove_nothing() {
+    let mut database = customers();
+    assert_eq!(execute(&mut database, "delete from customers where ID > 10").unwrap(), Some(0));
+    assert_eq!(rows(&mut database, "get * from customers").unwrap().len(), 3);
+}
+
+#[test]
+fn deletes_without_a_condition_remove_every_row() {
+    let mut database = customers();
+    assert_eq!(execute(&mut database, "delete from customers").unwrap(), Some(3));
+    assert_eq!(rows(&mut database, "get * from customers").unwrap(), Vec::<Vec<FieldValue>>::new());
+    assert_eq!(columns(&mut database, "get * from customers").unwrap(), vec!["Name", "ID"]);
+}
+
+#[test]
+fn a_delete_whose_condition_fails_removes_nothing() {
+    let mut database = customers();
+    assert!(execute(&mut database, "delete from customers where ID = 3 or Name > 1").is_err());
+    assert_eq!(rows(&mut database, "get * from customers").unwrap().len(), 3);
+}
+
+#[test]
+fn updates_change_the_rows_that_match() {
+    let mut database = customers();
+    assert_eq!(execute(&mut database, "update customers set Name = \"j\" where ID >= 2").unwrap(), Some(2));
+    assert_eq!(rows(&mut database, "get Name from customers").unwrap(), vec![row!["james"], row!["j"], row!["j"]]);
+}
+
+#[test]
+fn updates_without_a_condition_change_every_row() {
+    let mut database = customers();
+    assert_eq!(execute(&mut database, "update customers set ID = 0, Name = none").unwrap(), Some(3));
+    assert_eq!(rows(&mut database, "get * from customers").unwrap(), vec![row![FieldValue::None, 0]; 3]);
+}
+
+#[test]
+fn updates_can_use_the_rows_own_values() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table stats [id: number, hits: number, misses: number]").unwrap();
+    execute(&mut database, "put [1, 0, 5] in stats").unwrap();
+    execute(&mut database, "put [2, 7, 1] in stats").unwrap();
+    execute(&mut database, "update stats set hits = hits + 1 where id = 1").unwrap();
+    execute(&mut database, "update stats set hits = hits + 1 where id = 1").unwrap();
+    assert_eq!(rows(&mut database, "get hits from stats").unwrap(), vec![row![2], row![7]]);
+    execute(&mut database, "update stats set misses = hits * 10 + misses").unwrap();
+    assert_eq!(rows(&mut database, "get misses from stats").unwrap(), vec![row![25], row![71]]);
+}
+
+#[test]
+fn every_assignment_sees_the_row_before_the_update() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table pairs [a: number, b: number]").unwrap();
+    execute(&mut database, "put [1, 2] in pairs").unwrap();
+    execute(&mut database, "update pairs set a = b, b = a").unwrap();
+    assert_eq!(rows(&mut database, "get a, b from pairs").unwrap(), vec![row![2, 1]]);
+    execute(&mut database, "update pairs set a = a + 1, b = a + 1").unwrap();
+    assert_eq!(rows(&mut database, "get a, b from pairs").unwrap(), vec![row![3, 3]]);
+}
+
+#[test]
+fn a_failed_update_changes_nothing() {
+    let mut database = customers();
+    // Only the last row's value can't be worked out.
+    assert!(matches!(execute(&mut database, "update customers set Name = \"x\", ID = 10 / (3 - ID)"),
+                     Err(CoilError::DivisionByZero)));
+    assert!(matches!(execute(&mut database, "update customers set Name = \"x\", ID = \"one\" where ID = 3"),
+                     Err(CoilError::MismatchedTypes{..})));
+    assert!(matches!(execute(&mut database, "update customers set age = 1"), Err(CoilError::ColumnDoesntExist(_))));
+    assert_eq!(rows(&mut database, "get * from customers").unwrap(), vec![row!["james", 1], row!["jim", 2], row!["jimmy", 3]]);
+}
+
+#[test]
+fn named_puts_can_give_values_in_any_order() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table users [ID: number primary auto, Name: text, status: text default \"new\", age: number]").unwrap();
+    execute(&mut database, "put (age = 30, Name = \"jim\") in users").unwrap();
+    execute(&mut database, "put (status = \"old\", Name = \"bob\", age = 20 + 5, ID = 7) in users").unwrap();
+    assert_eq!(rows(&mut database, "get * from users").unwrap(),
+               vec![row![1, "jim", "new", 30], row![7, "bob", "old", 25]]);
+}
+
+#[test]
+fn named_puts_only_take_the_tables_columns() {
+    let mut database = customers();
+    assert!(matches!(execute(&mut database, "put (Name = \"bob\", age = 4) in customers"),
+                     Err(CoilError::ColumnDoesntExist(column)) if column == "age"));
+    assert!(execute(&mut database, "put (Name = \"bob\", ID = ID) in customers").is_err());
+    assert_eq!(rows(&mut database, "get * from customers").unwrap().len(), 3);
+}
+
+#[test]
+fn truncate_removes_every_row_but_keeps_the_columns() {
+    let mut database = customers();
+    execute(&mut database, "create table orders [ID: number primary auto, total: number]").unwrap();
+    execute(&mut database, "put (total = 5) in orders").unwrap();
+    execute(&mut database, "put (total = 6) in orders").unwrap();
+    assert_eq!(execute(&mut database, "truncate orders").unwrap(), Some(2));
+    assert_eq!(rows(&mut database, "get count(*) from orders").unwrap(), vec![row![0]]);
+    assert_eq!(rows(&mut database, "describe orders").unwrap(),
+               vec![row!["ID", "number", "primary auto"], row!["total", "number", ""]]);
+    // Numbering starts again from the beginning.
+    execute(&mut database, "put (total = 7) in orders").unwrap();
+    assert_eq!(rows(&mut database, "get ID from orders").unwrap(), vec![row![1]]);
+    assert!(matches!(execute(&mut database, "truncate suppliers"), Err(CoilError::TableDoesntExist)));
+}
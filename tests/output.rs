@@ -0,0 +1,118 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn results_export_to_json() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table mixed [name: text, n: number, ok: bool]").unwrap();
+    execute(&mut database, "put [\"say \\\"hi\\\"\", 1, true] in mixed").unwrap();
+    execute(&mut database, "put [none, 2.5, none] in mixed").unwrap();
+    let result = database.run_query(Query::parse("get ok, n, name from mixed").unwrap()).unwrap();
+    assert_eq!(result.to_json(),
+               r#"[{"ok":true,"n":1,"name":"say \"hi\""},{"ok":null,"n":2.5,"name":null}]"#);
+    let result = database.run_query(Query::parse("get * from mixed where n > 5").unwrap()).unwrap();
+    assert_eq!(result.to_json(), "[]");
+}
+
+#[test]
+fn exports_keep_the_column_order() {
+    let mut database = customers();
+    let query = "get ID, Name from customers where ID < 3";
+    let first = database.run_query(Query::parse(query).unwrap()).unwrap();
+    let (csv, json) = (first.to_csv(), first.to_json());
+    let second = database.run_query(Query::parse(query).unwrap()).unwrap();
+    assert_eq!(csv, second.to_csv());
+    assert_eq!(json, second.to_json());
+    assert_eq!(csv, "ID,Name\n1,james\n2,jim\n");
+    assert_eq!(json, r#"[{"ID":1,"Name":"james"},{"ID":2,"Name":"jim"}]"#);
+}
+
+fn prices(float_precision: Option<usize>) -> Database {
+    let mut config = DatabaseConfig::in_memory();
+    config.float_precision = float_precision;
+    let mut database = Database::new(String::from("test"), config).unwrap();
+    execute(&mut database, "create table prices [item: text, price: number]").unwrap();
+    execute(&mut database, "put [\"tea\", 1.5] in prices").unwrap();
+    execute(&mut database, "put [\"cake\", 2.125] in prices").unwrap();
+    execute(&mut database, "put [\"pen\", 3] in prices").unwrap();
+    database
+}
+
+#[test]
+fn floats_are_shown_in_full_by_default() {
+    let mut database = prices(None);
+    let result = database.run_query(Query::parse("get * from prices").unwrap()).unwrap();
+    assert_eq!(result.to_csv(), "item,price\ntea,1.5\ncake,2.125\npen,3\n");
+    assert!(result.to_table_string().contains(" 2.125 "));
+}
+
+#[test]
+fn floats_can_be_shown_to_a_fixed_precision() {
+    let mut database = prices(Some(2));
+    let result = database.run_query(Query::parse("get * from prices").unwrap()).unwrap();
+    // Integers aren't floats, so they're left alone.
+    assert_eq!(result.to_csv(), "item,price\ntea,1.50\ncake,2.12\npen,3\n");
+    let table = result.to_table_string();
+    assert!(table.contains(" 1.50 ") && table.contains(" 2.12 "));
+    assert_eq!(FieldValue::Float(1.0).format(Some(2)), "1.00");
+    assert_eq!(FieldValue::Float(1.0).format(None), FieldValue::Float(1.0).to_string());
+}
+
+fn rendered(format: OutputFormat) -> String {
+    let mut config = DatabaseConfig::in_memory();
+    config.output_format = format;
+    let mut database = Database::new(String::from("test"), config).unwrap();
+    execute(&mut database, "create table customers [Name: text, ID: number]").unwrap();
+    execute(&mut database, "put [\"james\", 1] in customers").unwrap();
+    execute(&mut database, "put [\"jim\", 2] in customers").unwrap();
+    database.run_query(Query::parse("get * from customers").unwrap()).unwrap().to_table_string()
+}
+
+#[test]
+fn results_render_in_each_output_format() {
+    assert_eq!(rendered(OutputFormat::default()), rendered(OutputFormat::Bordered));
+    assert_eq!(rendered(OutputFormat::Compact), " Name   ID \n james  1 \n jim    2 \n");
+    assert_eq!(rendered(OutputFormat::Markdown), "| Name  | ID |\n|-------|----|\n| james | 1  |\n| jim   | 2  |\n");
+}
+
+fn markdown(database: &mut Database, query: &str) -> String {
+    let mut result = database.run_query(Query::parse(query).unwrap()).unwrap();
+    result.output_format = OutputFormat::Markdown;
+    result.to_table_string()
+}
+
+#[test]
+fn markdown_tables_have_a_line_under_the_header() {
+    let mut database = customers();
+    let output = markdown(&mut database, "get * from customers where ID = 2");
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with('|') && lines[0].contains(" Name ") && lines[0].contains(" ID "));
+    assert!(lines[1].chars().all(|c| c == '-' || c == '|'));
+    assert!(lines[2].contains(" jim ") && lines[2].contains(" 2 "));
+}
+
+#[test]
+fn pipes_in_markdown_cells_are_escaped() {
+    let mut database = customers();
+    execute(&mut database, "put [\"a|b\", 4] in customers").unwrap();
+    let output = markdown(&mut database, "get Name as `x|y` from customers where ID = 4");
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[0].contains(" x\\|y "));
+    assert!(lines[2].contains(" a\\|b "));
+    assert_eq!(lines[2].matches('|').count(), 3);
+}
+
+#[test]
+fn results_render_to_a_string() {
+    assert_eq!(rendered(OutputFormat::Bordered),
+               "+-------+----+\n\
+                | Name  | ID |\n\
+                +-------+----+\n\
+                | james | 1  |\n\
+                +-------+----+\n\
+                | jim   | 2  |\n\
+                +-------+----+\n");
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn binds_several_placeholders() {
+    let mut database = customers();
+    let mut query = Query::parse("get Name from customers where ID > ? and Name like ?").unwrap();
+    assert_eq!(query.placeholder_count(), 2);
+    query.bind_all(vec![FieldValue::from(1), FieldValue::from("jim%")]).unwrap();
+    let result = database.run_query(query).unwrap();
+    let names: Vec<&FieldValue> = result.rows.as_ref().unwrap().iter().map(|row| row.get("Name").unwrap()).collect();
+    assert_eq!(names, [&FieldValue::from("jim"), &FieldValue::from("jimmy")]);
+}
+
+#[test]
+fn binds_placeholders_in_an_in_list() {
+    let mut database = customers();
+    let mut query = Query::parse("get Name from customers where ID in [?, 3, ?]").unwrap();
+    query.bind(0, FieldValue::from(1)).unwrap();
+    query.bind(1, FieldValue::from(2)).unwrap();
+    let result = database.run_query(query).unwrap();
+    assert_eq!(result.rows.unwrap().len(), 3);
+}
+
+#[test]
+fn binds_put_values_and_assignments() {
+    let mut database = customers();
+    let mut query = Query::parse("put [?, ?] in customers").unwrap();
+    query.bind_all(vec![FieldValue::from("o'brien\"; delete table customers"), FieldValue::from(4)]).unwrap();
+    database.run_query(query).unwrap();
+    let mut query = Query::parse("update customers set ID = ? where ID = ?").unwrap();
+    query.bind_all(vec![FieldValue::from(5), FieldValue::from(4)]).unwrap();
+    database.run_query(query).unwrap();
+    assert_eq!(rows(&mut database, "get Name from customers where ID = 5").unwrap(),
+               vec![row!["o'brien\"; delete table customers"]]);
+}
+
+#[test]
+fn binds_placeholders_in_an_explained_query() {
+    let mut database = customers();
+    let mut query = Query::parse("explain get * from customers where ID = ?").unwrap();
+    query.bind(0, FieldValue::from(1)).unwrap();
+    let result = database.run_query(query).unwrap();
+    assert!(!result.explanation.unwrap().contains("Placeholder"));
+}
+
+#[test]
+fn unbound_placeholders_are_an_error() {
+    let mut database = customers();
+    let mut query = Query::parse("get * from customers where ID = ? or ID = ?").unwrap();
+    query.bind(1, FieldValue::from(1)).unwrap();
+    assert!(matches!(database.run_query(query), Err(CoilError::UnboundPlaceholder(0))));
+}
+
+#[test]
+fn binding_the_wrong_placeholders_is_an_error() {
+    let mut query = Query::parse("get * from customers where ID = ?").unwrap();
+    assert!(matches!(query.bind(1, FieldValue::from(1)), Err(CoilError::NoSuchPlaceholder(1))));
+    assert!(matches!(query.bind_all(Vec::new()), Err(CoilError::NotEnoughValues)));
+    assert!(matches!(query.bind_all(vec![FieldValue::from(1), FieldValue::from(2)]), Err(CoilError::TooManyValues)));
+}
+
+#[test]
+fn invalid_query_text_is_an_error() {
+    assert!(matches!(Query::parse("get * form customers"), Err(CoilError::InvalidQuery(_))));
+    assert!(matches!(Query::parse("get \"unterminated"), Err(CoilError::InvalidQuery(_))));
+}
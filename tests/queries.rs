@@ -0,0 +1,147 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn limit_caps_the_rows_returned() {
+    let mut database = customers();
+    assert_eq!(rows(&mut database, "get ID from customers limit 2").unwrap(), vec![row![1], row![2]]);
+    assert_eq!(rows(&mut database, "get ID from customers where ID > 1 limit 1").unwrap(), vec![row![2]]);
+    assert_eq!(rows(&mut database, "get ID from customers limit 0").unwrap(), Vec::<Vec<FieldValue>>::new());
+    assert_eq!(rows(&mut database, "get ID from customers limit 10").unwrap().len(), 3);
+    assert!(Query::parse("get * from customers limit -1").is_err());
+}
+
+#[test]
+fn order_by_sorts_the_rows() {
+    let mut database = customers();
+    execute(&mut database, "put [\"alice\", none] in customers").unwrap();
+    execute(&mut database, "put [\"bob\", 2.5] in customers").unwrap();
+    assert_eq!(rows(&mut database, "get ID from customers order by ID").unwrap(),
+               vec![row![1], row![2], row![2.5], row![3], row![FieldValue::None]]);
+    assert_eq!(rows(&mut database, "get ID from customers order by ID desc").unwrap(),
+               vec![row![FieldValue::None], row![3], row![2.5], row![2], row![1]]);
+    assert_eq!(rows(&mut database, "get Name from customers order by Name asc limit 2").unwrap(),
+               vec![row!["alice"], row!["bob"]]);
+}
+
+#[test]
+fn ordering_by_a_missing_column_is_an_error() {
+    let mut database = customers();
+    assert!(matches!(rows(&mut database, "get * from customers order by age"), Err(CoilError::ColumnDoesntExist(_))));
+}
+
+#[test]
+fn only_the_selected_columns_are_returned_in_the_order_given() {
+    let mut database = customers();
+    assert_eq!(columns(&mut database, "get ID, Name from customers").unwrap(), vec!["ID", "Name"]);
+    assert_eq!(rows(&mut database, "get ID, Name from customers where ID = 1").unwrap(), vec![row![1, "james"]]);
+    assert_eq!(columns(&mut database, "get * from customers").unwrap(), vec!["Name", "ID"]);
+    assert!(matches!(rows(&mut database, "get Name, age from customers"), Err(CoilError::ColumnDoesntExist(_))));
+}
+
+#[test]
+fn distinct_leaves_out_repeated_rows() {
+    let mut database = customers();
+    execute(&mut database, "create table visits [Name: text, page: text, at: number]").unwrap();
+    execute(&mut database, "put [\"jim\", \"home\", 1] in visits").unwrap();
+    execute(&mut database, "put [\"jim\", \"home\", 2] in visits").unwrap();
+    execute(&mut database, "put [\"bob\", \"home\", 3] in visits").unwrap();
+    execute(&mut database, "put [\"jim\", \"cart\", 4] in visits").unwrap();
+    assert_eq!(rows(&mut database, "get distinct Name from visits").unwrap(), vec![row!["jim"], row!["bob"]]);
+    // Rows that only differ in `at`, which isn't
+    // selected, are the same row.
+    assert_eq!(rows(&mut database, "get distinct Name, page from visits").unwrap(),
+               vec![row!["jim", "home"], row!["bob", "home"], row!["jim", "cart"]]);
+    assert_eq!(rows(&mut database, "get distinct * from visits").unwrap().len(), 4);
+}
+
+#[test]
+fn offset_skips_rows_for_paging() {
+    let mut database = customers();
+    for id in 4..=10 {
+        execute(&mut database, &format!("put [\"c{}\", {}] in customers", id, 14 - id)).unwrap();
+    }
+    let page = |database: &mut Database, n: usize| {
+        rows(database, &format!("get ID from customers order by ID limit 4 offset {}", n * 4)).unwrap()
+    };
+    assert_eq!(page(&mut database, 0), vec![row![1], row![2], row![3], row![4]]);
+    assert_eq!(page(&mut database, 1), vec![row![5], row![6], row![7], row![8]]);
+    assert_eq!(page(&mut database, 2), vec![row![9], row![10]]);
+    assert_eq!(page(&mut database, 3), Vec::<Vec<FieldValue>>::new());
+    assert_eq!(rows(&mut database, "get ID from customers offset 9").unwrap(), vec![row![4]]);
+}
+
+#[test]
+fn aliases_rename_the_result_columns() {
+    let mut database = customers();
+    let query = "get Name as customer, ID as id, ID from customers where ID = 2";
+    assert_eq!(columns(&mut database, query).unwrap(), vec!["customer", "id", "ID"]);
+    assert_eq!(rows(&mut database, query).unwrap(), vec![row!["jim", 2, 2]]);
+    let result = database.run_query(Query::parse(query).unwrap()).unwrap();
+    assert!(result.to_table_string().lines().nth(1).unwrap().contains(" customer "));
+    assert_eq!(columns(&mut database, "get count(*) as total from customers").unwrap(), vec!["total"]);
+}
+
+#[test]
+fn two_columns_cant_have_the_same_alias() {
+    let mut database = customers();
+    assert!(columns(&mut database, "get Name as x, ID as x from customers").is_err());
+}
+
+#[test]
+fn getting_from_an_empty_table_returns_no_rows() {
+    let mut database = customers();
+    execute(&mut database, "create table orders [ID: number]").unwrap();
+    assert_eq!(rows(&mut database, "get * from orders").unwrap(), Vec::<Vec<FieldValue>>::new());
+    assert_eq!(rows(&mut database, "get ID from orders where ID = 1 order by ID limit 1").unwrap(), Vec::<Vec<FieldValue>>::new());
+    let result = database.run_query(Query::parse("get * from orders").unwrap()).unwrap();
+    assert!(result.to_table_string().contains("ID"));
+}
+
+#[test]
+fn results_without_rows_still_print() {
+    let result = QueryResult::new(Operation::Get);
+    assert_eq!(result.to_table_string(), "No rows\n");
+    assert_eq!(result.to_csv(), "");
+    assert_eq!(result.to_json(), "[]");
+}
+
+#[test]
+fn get_exists_says_whether_any_row_matches() {
+    let mut database = customers();
+    assert_eq!(rows(&mut database, "get exists from customers where ID = 2").unwrap(), vec![row![true]]);
+    assert_eq!(rows(&mut database, "get exists from customers where ID = 20").unwrap(), vec![row![false]]);
+    assert_eq!(rows(&mut database, "get exists from customers").unwrap(), vec![row![true]]);
+    assert_eq!(columns(&mut database, "get exists from customers where ID = 2").unwrap(), ["exists"]);
+    execute(&mut database, "create table orders [ID: number]").unwrap();
+    assert_eq!(rows(&mut database, "get exists from orders where ID = 2").unwrap(), vec![row![false]]);
+    assert_eq!(rows(&mut database, "get exists from orders").unwrap(), vec![row![false]]);
+}
+
+#[test]
+fn tables_can_be_checked_for_a_matching_row() {
+    let mut database = customers();
+    let condition = |text: &str| *Query::parse(&format!("get * from t where {}", text)).unwrap().condition.unwrap();
+    let table = database.get_table(String::from("customers")).unwrap();
+    assert!(table.any(&condition("ID = 2"), None).unwrap());
+    assert!(!table.any(&condition("ID = 20"), None).unwrap());
+    execute(&mut database, "delete from customers").unwrap();
+    let table = database.get_table(String::from("customers")).unwrap();
+    assert!(!table.any(&condition("ID = 2"), None).unwrap());
+}
+
+#[test]
+fn get_exists_stops_at_the_scan_limit() {
+    let mut config = DatabaseConfig::in_memory();
+    config.max_scan_rows = Some(1);
+    let mut database = Database::new(String::from("test"), config).unwrap();
+    execute(&mut database, "create table customers [Name: text, ID: number]").unwrap();
+    execute(&mut database, "put [\"james\", 1] in customers").unwrap();
+    execute(&mut database, "put [\"jim\", 2] in customers").unwrap();
+    assert!(matches!(rows(&mut database, "get exists from customers where ID = 20"), Err(CoilError::ScanLimitExceeded(1))));
+    assert!(matches!(rows(&mut database, "get * from customers where ID = 20"), Err(CoilError::ScanLimitExceeded(1))));
+    // A match in the first row is found before the limit.
+    assert_eq!(rows(&mut database, "get exists from customers where ID = 1").unwrap(), vec![row![true]]);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+// A database that gives up after looking at `max` rows,
+// with a table `small` of 3 rows and `big` of 20.
+fn capped(max: usize) -> Database {
+    let mut config = DatabaseConfig::in_memory();
+    config.max_scan_rows = Some(max);
+    let mut database = Database::new(String::from("capped"), config).unwrap();
+    execute(&mut database, "create table small [id: number]").unwrap();
+    execute(&mut database, "create table big [id: number]").unwrap();
+    for id in 0..20 {
+        if id < 3 {
+            execute(&mut database, &format!("put [{}] in small", id)).unwrap();
+        }
+        execute(&mut database, &format!("put [{}] in big", id)).unwrap();
+    }
+    database
+}
+
+#[test]
+fn scans_within_the_limit_succeed() {
+    let mut database = capped(10);
+    assert_eq!(rows(&mut database, "get * from small").unwrap().len(), 3);
+}
+
+#[test]
+fn scans_over_the_limit_fail() {
+    let mut database = capped(10);
+    assert!(matches!(rows(&mut database, "get * from big"), Err(CoilError::ScanLimitExceeded(10))));
+    // Even if only one row matches.
+    assert!(matches!(rows(&mut database, "get * from big where id = 19"), Err(CoilError::ScanLimitExceeded(10))));
+}
+
+#[test]
+fn limited_scans_stop_early() {
+    let mut database = capped(10);
+    assert_eq!(rows(&mut database, "get * from big limit 5").unwrap().len(), 5);
+}
+
+#[test]
+fn joins_count_every_pair_of_rows() {
+    let mut database = capped(10);
+    // 3 × 20 pairs, even though only 3 of them match.
+    assert!(matches!(rows(&mut database, "get * from small join big on small.id = big.id"),
+                     Err(CoilError::ScanLimitExceeded(10))));
+    let mut database = capped(100);
+    assert_eq!(rows(&mut database, "get * from small join big on small.id = big.id").unwrap().len(), 3);
+}
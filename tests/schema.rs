@@ -0,0 +1,67 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+// Runs every line of `ddl` against a fresh database,
+// and returns what that database's DDL comes out as.
+fn rebuilt(ddl: &str) -> String {
+    let mut database = Database::in_memory(String::from("copy")).unwrap();
+    for line in ddl.lines() {
+        execute(&mut database, line.trim_end_matches(';')).unwrap();
+    }
+    database.to_ddl()
+}
+
+#[test]
+fn the_ddl_recreates_the_tables() {
+    let mut database = customers();
+    execute(&mut database, "create table orders [ID: number primary auto, total: number default 0]").unwrap();
+    let ddl = database.to_ddl();
+    assert_eq!(ddl, "create table customers [Name: text, ID: number];\n\
+                     create table orders [ID: number primary auto, total: number default 0];\n");
+    assert_eq!(rebuilt(&ddl), ddl);
+}
+
+#[test]
+fn float_defaults_stay_floats() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table prices [item: text, price: number default 1.0, tax: number default 0.25]").unwrap();
+    let ddl = database.to_ddl();
+    assert_eq!(ddl, "create table prices [item: text, price: number default 1.0, tax: number default 0.25];\n");
+    assert_eq!(rebuilt(&ddl), ddl);
+
+    let mut copy = Database::in_memory(String::from("copy")).unwrap();
+    execute(&mut copy, ddl.trim_end().trim_end_matches(';')).unwrap();
+    execute(&mut copy, "put (item = \"tea\") in prices").unwrap();
+    assert!(matches!(rows(&mut copy, "get price from prices").unwrap()[0][0], FieldValue::Float(_)));
+}
+
+#[test]
+fn keywords_are_written_in_backticks() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table `order` [`where`: text, `Group`: number, `first name`: text, total: number]").unwrap();
+    let ddl = database.to_ddl();
+    assert_eq!(ddl, "create table `order` [`where`: text, `Group`: number, `first name`: text, total: number];\n");
+    assert_eq!(rebuilt(&ddl), ddl);
+}
+
+#[test]
+fn describe_lists_a_tables_columns() {
+    let mut database = customers();
+    execute(&mut database, "create table orders [ID: number primary auto, total: number default 0, paid: bool, note: text]").unwrap();
+    assert_eq!(columns(&mut database, "describe orders").unwrap(), vec!["column", "type", "modifiers"]);
+    assert_eq!(rows(&mut database, "describe orders").unwrap(),
+               vec![row!["ID", "number", "primary auto"],
+                    row!["total", "number", "default 0"],
+                    row!["paid", "bool", ""],
+                    row!["note", "text", ""]]);
+    assert!(matches!(rows(&mut database, "describe suppliers"), Err(CoilError::TableDoesntExist)));
+}
+
+#[test]
+fn show_tables_lists_every_table() {
+    let mut database = customers();
+    execute(&mut database, "create table orders [ID: number]").unwrap();
+    assert_eq!(rows(&mut database, "show tables").unwrap(), vec![row!["customers"], row!["orders"]]);
+}
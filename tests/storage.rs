@@ -0,0 +1,120 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+// A database saved in `directory`, holding the
+// REPL's `customers` table.
+fn saved(directory: &std::path::Path, format: StorageFormat) -> Database {
+    let mut database = Database::new(String::from("shop"), DatabaseConfig::with_format(directory.to_path_buf(), format)).unwrap();
+    execute(&mut database, "create table customers [Name: text, ID: number]").unwrap();
+    execute(&mut database, "put [\"james\", 1] in customers").unwrap();
+    execute(&mut database, "put [\"jim\", 2] in customers").unwrap();
+    database
+}
+
+#[test]
+fn a_database_is_saved_in_its_directory_under_its_name() {
+    let directory = directory("saved-path");
+    let database = saved(&directory.join("data"), StorageFormat::Json);
+    assert_eq!(database.file_path(), directory.join("data").join("shop"));
+    database.save().unwrap();
+
+    let mut loaded = Database::from_file(&database.file_path()).unwrap();
+    assert_eq!(loaded.name(), "shop");
+    assert_eq!(rows(&mut loaded, "get * from customers").unwrap(), vec![row!["james", 1], row!["jim", 2]]);
+}
+
+#[test]
+fn loading_a_garbage_file_is_an_error() {
+    let directory = directory("garbage");
+    std::fs::write(directory.join("json"), "{\"name\": \"shop\", \"tables\": [").unwrap();
+    std::fs::write(directory.join("binary"), b"COIL\xff\xff\xff\xff").unwrap();
+    for name in ["json", "binary"] {
+        assert!(matches!(Database::from_file(&directory.join(name)), Err(CoilError::DeserializationFailed(_))));
+    }
+    assert!(matches!(Database::from_file(&directory.join("missing")), Err(CoilError::DatabaseDoesntExist)));
+}
+
+#[test]
+fn both_formats_round_trip_every_kind_of_value() {
+    let directory = directory("formats");
+    for (name, format) in [("json", StorageFormat::Json), ("binary", StorageFormat::Binary)] {
+        let mut database = Database::new(String::from(name), DatabaseConfig::with_format(directory.clone(), format)).unwrap();
+        execute(&mut database, "create table values [t: text, n: number primary, b: bool]").unwrap();
+        execute(&mut database, "put [\"jim \\\"j\\\"\\n\", 1, true] in values").unwrap();
+        execute(&mut database, "put [none, 2.5, false] in values").unwrap();
+        execute(&mut database, "put [\"\", -3, none] in values").unwrap();
+        database.save().unwrap();
+
+        let mut loaded = Database::from_file(&database.file_path()).unwrap();
+        assert_eq!(loaded.to_ddl(), database.to_ddl());
+        assert_eq!(rows(&mut loaded, "get * from values").unwrap(),
+                   vec![row!["jim \"j\"\n", 1, true], row![FieldValue::None, 2.5, false], row!["", -3, FieldValue::None]]);
+        assert!(matches!(execute(&mut loaded, "put [\"x\", 2.5, true] in values"), Err(CoilError::DuplicateKey)));
+    }
+    assert!(std::fs::read(directory.join("binary")).unwrap().starts_with(b"COIL"));
+    assert!(std::fs::read(directory.join("json")).unwrap().starts_with(b"{"));
+}
+
+#[test]
+fn autosaved_changes_survive_a_reload() {
+    let directory = directory("autosave");
+    let mut config = DatabaseConfig::new(directory.clone());
+    config.autosave = true;
+    let mut database = Database::new(String::from("shop"), config).unwrap();
+    execute(&mut database, "create table customers [Name: text, ID: number]").unwrap();
+    execute(&mut database, "put [\"jim\", 2] in customers").unwrap();
+    let path = database.file_path();
+    drop(database);
+
+    let mut loaded = Database::from_file(&path).unwrap();
+    assert_eq!(rows(&mut loaded, "get * from customers").unwrap(), vec![row!["jim", 2]]);
+}
+
+#[test]
+fn autosave_waits_for_a_transaction_to_be_committed() {
+    let directory = directory("autosave-transaction");
+    let mut config = DatabaseConfig::new(directory.clone());
+    config.autosave = true;
+    let mut database = Database::new(String::from("shop"), config).unwrap();
+    execute(&mut database, "create table customers [Name: text, ID: number]").unwrap();
+    execute(&mut database, "begin").unwrap();
+    execute(&mut database, "put [\"jim\", 2] in customers").unwrap();
+    assert_eq!(rows(&mut Database::from_file(&database.file_path()).unwrap(), "get * from customers").unwrap().len(), 0);
+    execute(&mut database, "commit").unwrap();
+    assert_eq!(rows(&mut Database::from_file(&database.file_path()).unwrap(), "get * from customers").unwrap().len(), 1);
+}
+
+#[test]
+fn in_memory_databases_never_touch_the_disk() {
+    let name = format!("coil-in-memory-{}", std::process::id());
+    let mut config = DatabaseConfig::in_memory();
+    config.autosave = true;
+    let mut database = Database::new(name.clone(), config).unwrap();
+    execute(&mut database, "create table customers [Name: text]").unwrap();
+    execute(&mut database, "put [\"jim\"] in customers").unwrap();
+    assert_eq!(rows(&mut database, "get * from customers").unwrap(), vec![row!["jim"]]);
+    assert!(matches!(database.save(), Err(CoilError::SaveFailed(_))));
+    assert!(!database.file_path().exists());
+    assert!(!std::path::Path::new(&name).exists());
+}
+
+#[test]
+fn indexes_are_rebuilt_on_load() {
+    let directory = directory("indexes");
+    for format in [StorageFormat::Json, StorageFormat::Binary] {
+        let mut database = saved(&directory, format);
+        database.get_table_mut(String::from("customers")).unwrap().create_index("ID").unwrap();
+        database.save().unwrap();
+
+        let mut loaded = Database::from_file(&database.file_path()).unwrap();
+        assert_eq!(loaded.get_table(String::from("customers")).unwrap().indexed_columns(), ["ID"]);
+        let explained = loaded.run_query(Query::parse("explain get * from customers where ID = 2").unwrap()).unwrap();
+        assert!(explained.explanation.unwrap().starts_with("index lookup on `ID` in `customers`\n"));
+        // The index has the rows in it, not just its name.
+        assert_eq!(rows(&mut loaded, "get Name from customers where ID = 2").unwrap(), vec![row!["jim"]]);
+        execute(&mut loaded, "put [\"jimmy\", 3] in customers").unwrap();
+        assert_eq!(rows(&mut loaded, "get Name from customers where ID = 3").unwrap(), vec![row!["jimmy"]]);
+    }
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+// A table `numbers` with a single column, `x`,
+// holding `values`.
+fn numbers(values: &[i64]) -> Table {
+    let mut table = Table::new(String::from("numbers"), vec![Column::new(String::from("x"), FieldType::Number)]);
+    for value in values {
+        table.new_row(row![*value]).unwrap();
+    }
+    table
+}
+
+fn condition(text: &str) -> Expression {
+    *Query::parse(&format!("get * from t where {}", text)).unwrap().condition.unwrap()
+}
+
+fn xs(rows: Vec<Row>) -> Vec<i64> {
+    rows.iter().map(|row| row.get_int("x").unwrap()).collect()
+}
+
+#[test]
+fn rows_are_only_read_as_theyre_needed() {
+    // Giving up after 5 rows would be an error if
+    // every row were read before the first 3 came out.
+    let table = numbers(&(1..=10_000).collect::<Vec<i64>>());
+    assert_eq!(xs(table.scan_rows(None, Some(3), Some(5)).unwrap()), [1, 2, 3]);
+    assert!(matches!(table.scan_rows(None, Some(6), Some(5)), Err(CoilError::ScanLimitExceeded(5))));
+}
+
+#[test]
+fn rows_after_the_ones_taken_arent_checked() {
+    // Checking the row holding 0 would divide by zero.
+    let table = numbers(&[1, 2, 3, 4, 0, 6]);
+    let condition = condition("12 / x > 1");
+    let first: Vec<Row> = table.iter_rows(Some(&condition)).take(3).collect::<Result<_, _>>().unwrap();
+    assert_eq!(first.len(), 3);
+    assert_eq!(table.get_rows(Some(condition.clone()), Some(4)).unwrap().len(), 4);
+    assert!(matches!(table.get_rows(Some(condition), None), Err(CoilError::DivisionByZero)));
+}
+
+#[test]
+fn indexed_lookups_find_the_same_rows_as_scans() {
+    let values: Vec<i64> = (0..200).map(|i| i % 7).collect();
+    let plain = numbers(&values);
+    let mut indexed = numbers(&values);
+    indexed.create_index("x").unwrap();
+    assert_eq!(indexed.indexed_columns(), ["x"]);
+
+    for text in ["x = 3", "3 = x", "x = 3.0", "x = 9", "x = 3 and x > 1", "x > 1 and x = 5"] {
+        let condition = condition(text);
+        assert_eq!(indexed.plan(Some(&condition)), "index lookup on `x` in `numbers`", "{}", text);
+        assert_eq!(plain.plan(Some(&condition)), "full scan of `numbers`", "{}", text);
+        assert_eq!(xs(indexed.get_rows(Some(condition.clone()), None).unwrap()),
+                   xs(plain.get_rows(Some(condition), None).unwrap()), "{}", text);
+    }
+    for text in ["x > 3", "x = 3 or x = 4", "x != 3"] {
+        assert_eq!(indexed.plan(Some(&condition(text))), "full scan of `numbers`", "{}", text);
+    }
+}
+
+#[test]
+fn indexes_keep_up_with_changes() {
+    let mut database = customers();
+    database.get_table_mut(String::from("customers")).unwrap().create_index("ID").unwrap();
+    execute(&mut database, "put [\"bob\", 2] in customers").unwrap();
+    execute(&mut database, "update customers set ID = 2 where Name = \"james\"").unwrap();
+    execute(&mut database, "delete from customers where Name = \"jim\"").unwrap();
+    assert_eq!(rows(&mut database, "get Name from customers where ID = 2").unwrap(), vec![row!["james"], row!["bob"]]);
+    assert_eq!(rows(&mut database, "get Name from customers where ID = 1").unwrap(), Vec::<Vec<FieldValue>>::new());
+}
+
+#[test]
+fn typed_getters_only_return_values_of_their_type() {
+    let mut table = Table::new(String::from("t"), vec![Column::new(String::from("t"), FieldType::Text),
+                                                       Column::new(String::from("i"), FieldType::Number),
+                                                       Column::new(String::from("f"), FieldType::Number),
+                                                       Column::new(String::from("b"), FieldType::Boolean),
+                                                       Column::new(String::from("none"), FieldType::Text)]);
+    table.new_row(row!["jim", 2, 2.5, true, FieldValue::None]).unwrap();
+    let row = &table.all_rows()[0];
+
+    assert_eq!(row.get_text("t"), Some("jim"));
+    assert_eq!(row.get_int("i"), Some(2));
+    assert_eq!(row.get_float("f"), Some(2.5));
+    assert_eq!(row.get_float("i"), Some(2.0));
+    assert_eq!(row.get_bool("b"), Some(true));
+
+    assert_eq!(row.get_text("i"), None);
+    assert_eq!(row.get_int("f"), None);
+    assert_eq!(row.get_int("t"), None);
+    assert_eq!(row.get_float("t"), None);
+    assert_eq!(row.get_bool("i"), None);
+
+    for field in ["none", "missing"] {
+        assert_eq!(row.get_text(field), None);
+        assert_eq!(row.get_int(field), None);
+        assert_eq!(row.get_float(field), None);
+        assert_eq!(row.get_bool(field), None);
+    }
+}
+
+#[test]
+fn row_counts() {
+    let mut table = numbers(&[]);
+    assert_eq!(table.row_count(), 0);
+    assert!(table.is_empty());
+    table.new_row(row![1]).unwrap();
+    table.new_row(row![2]).unwrap();
+    assert_eq!(table.row_count(), 2);
+    assert!(!table.is_empty());
+
+    let columnless = Table::new(String::from("t"), Vec::new());
+    assert_eq!(columnless.row_count(), 0);
+    assert!(columnless.is_empty());
+    assert!(columnless.all_rows().is_empty());
+}
+
+#[test]
+fn bulk_inserts_add_every_row() {
+    let mut table = numbers(&[1]);
+    assert_eq!(table.new_rows(vec![row![2], row![3], row![FieldValue::None]]).unwrap(), 3);
+    assert_eq!(table.row_count(), 4);
+}
+
+#[test]
+fn a_failed_bulk_insert_leaves_the_table_as_it_was() {
+    let mut table = Table::new(String::from("t"), vec![Column::new(String::from("x"), FieldType::Number),
+                                                       Column::new(String::from("y"), FieldType::Text)]);
+    table.new_row(row![1, "a"]).unwrap();
+    let before = table.to_ddl();
+
+    assert!(matches!(table.new_rows(vec![row![2, "b"], row![3, 3], row![4, "d"]]), Err(CoilError::MismatchedTypes{..})));
+    assert!(matches!(table.new_rows(vec![row![2, "b"], row![3]]), Err(CoilError::NotEnoughValues)));
+    assert_eq!(table.row_count(), 1);
+    assert_eq!(table.get_column("y").unwrap().texts().collect::<Vec<Option<&str>>>(), [Some("a")]);
+    assert_eq!(table.to_ddl(), before);
+}
+
+#[test]
+fn bulk_inserts_check_keys_within_the_batch() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table users [x: number primary]").unwrap();
+    let table = database.get_table_mut(String::from("users")).unwrap();
+    table.new_row(row![1]).unwrap();
+    assert!(matches!(table.new_rows(vec![row![2], row![3], row![2]]), Err(CoilError::DuplicateKey)));
+    assert!(matches!(table.new_rows(vec![row![2], row![1]]), Err(CoilError::DuplicateKey)));
+    assert_eq!(xs(table.all_rows()), [1]);
+}
+
+#[test]
+fn all_rows_returns_every_row_in_order() {
+    assert_eq!(xs(numbers(&[3, 1, 2]).all_rows()), [3, 1, 2]);
+    assert!(numbers(&[]).all_rows().is_empty());
+    // Without any columns, there's nothing to count
+    // rows from.
+    assert!(Table::new(String::from("empty"), Vec::new()).all_rows().is_empty());
+}
+
+#[test]
+fn get_rows_matches_all_rows_without_a_condition() {
+    let table = numbers(&[1, 2, 3]);
+    assert_eq!(xs(table.get_rows(None, None).unwrap()), xs(table.all_rows()));
+    assert!(numbers(&[]).get_rows(None, None).unwrap().is_empty());
+}
+
+#[test]
+fn columns_can_be_read_as_their_values_type() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table mixed [n: number, t: text, b: bool]").unwrap();
+    execute(&mut database, "put [1, \"a\", true] in mixed").unwrap();
+    execute(&mut database, "put [none, none, none] in mixed").unwrap();
+    execute(&mut database, "put [2.5, \"b\", false] in mixed").unwrap();
+    let table = database.get_table(String::from("mixed")).unwrap();
+    let (n, t, b) = (table.get_column("n").unwrap(), table.get_column("t").unwrap(), table.get_column("b").unwrap());
+    // 2.5 isn't an integer, but 1 can be a float.
+    assert_eq!(n.ints().collect::<Vec<_>>(), [Some(1), None, None]);
+    assert_eq!(n.floats().collect::<Vec<_>>(), [Some(1.0), None, Some(2.5)]);
+    assert_eq!(t.texts().collect::<Vec<_>>(), [Some("a"), None, Some("b")]);
+    assert_eq!(b.booleans().collect::<Vec<_>>(), [Some(true), None, Some(false)]);
+    assert_eq!(t.ints().collect::<Vec<_>>(), [None, None, None]);
+    assert_eq!(n.ints().flatten().sum::<i64>(), 1);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn committed_changes_stay() {
+    let mut database = customers();
+    execute(&mut database, "begin").unwrap();
+    assert!(database.in_transaction());
+    execute(&mut database, "put [\"bob\", 4] in customers").unwrap();
+    execute(&mut database, "delete from customers where ID = 1").unwrap();
+    execute(&mut database, "commit").unwrap();
+    assert!(!database.in_transaction());
+    assert_eq!(rows(&mut database, "get ID from customers").unwrap(), vec![row![2], row![3], row![4]]);
+}
+
+#[test]
+fn rolled_back_changes_are_undone() {
+    let mut database = customers();
+    let before = rows(&mut database, "get * from customers").unwrap();
+    execute(&mut database, "begin").unwrap();
+    execute(&mut database, "put [\"bob\", 4] in customers").unwrap();
+    execute(&mut database, "update customers set Name = \"x\"").unwrap();
+    execute(&mut database, "create table orders [ID: number]").unwrap();
+    execute(&mut database, "delete table customers").unwrap();
+    execute(&mut database, "rollback").unwrap();
+    assert!(!database.in_transaction());
+    assert_eq!(rows(&mut database, "get * from customers").unwrap(), before);
+    assert_eq!(database.table_names(), ["customers"]);
+}
+
+#[test]
+fn transactions_dont_nest() {
+    let mut database = customers();
+    assert!(matches!(execute(&mut database, "commit"), Err(CoilError::NoTransaction)));
+    assert!(matches!(execute(&mut database, "rollback"), Err(CoilError::NoTransaction)));
+    execute(&mut database, "begin").unwrap();
+    assert!(matches!(execute(&mut database, "begin"), Err(CoilError::TransactionAlreadyStarted)));
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+#[test]
+fn boolean_columns_hold_true_and_false() {
+    let mut database = customers();
+    execute(&mut database, "create table flags [Name: text, active: bool]").unwrap();
+    execute(&mut database, "put [\"a\", true] in flags").unwrap();
+    execute(&mut database, "put [\"b\", false] in flags").unwrap();
+    assert_eq!(rows(&mut database, "get Name from flags where active").unwrap(), vec![row!["a"]]);
+    assert_eq!(rows(&mut database, "get Name from flags where not active").unwrap(), vec![row!["b"]]);
+    execute(&mut database, "put [\"c\", none] in flags").unwrap();
+    assert_eq!(rows(&mut database, "get Name from flags where active = true").unwrap(), vec![row!["a"]]);
+    assert_eq!(rows(&mut database, "get Name from flags where active = false").unwrap(), vec![row!["b"]]);
+    match execute(&mut database, "put [\"d\", 1] in flags") {
+        Err(CoilError::MismatchedTypes{column, expected: FieldType::Boolean, got}) => {
+            assert_eq!(column, "active");
+            assert_eq!(got, FieldValue::from(1));
+        },
+        other => { panic!("{:?}", other); }
+    }
+}
+
+#[test]
+fn a_row_with_a_mismatched_value_isnt_added_to_any_column() {
+    let mut table = Table::new(String::from("t"), vec![Column::new(String::from("a"), FieldType::Text),
+                                                       Column::new(String::from("b"), FieldType::Number),
+                                                       Column::new(String::from("c"), FieldType::Boolean)]);
+    table.new_row(row!["x", 1, true]).unwrap();
+    assert!(matches!(table.new_row(row!["y", 2, "yes"]), Err(CoilError::MismatchedTypes{..})));
+    assert_eq!(table.row_count(), 1);
+    for name in ["a", "b", "c"] {
+        assert_eq!(table.get_column(name).unwrap().texts().count(), 1, "{}", name);
+    }
+}
+
+#[test]
+fn mismatched_types_name_the_column() {
+    let mut database = customers();
+    let error = execute(&mut database, "put [\"bob\", \"four\"] in customers").unwrap_err();
+    match &error {
+        CoilError::MismatchedTypes{column, expected: FieldType::Number, got} => {
+            assert_eq!(column, "ID");
+            assert_eq!(*got, FieldValue::from("four"));
+        },
+        other => { panic!("{:?}", other); }
+    }
+    assert_eq!(error.to_string(), "`ID` is a number column, so it can't hold \"four\"");
+    assert!(matches!(execute(&mut database, "update customers set Name = 5"),
+                     Err(CoilError::MismatchedTypes{column, ..}) if column == "Name"));
+}
+
+#[test]
+fn values_convert_from_rust_types() {
+    assert!(matches!(FieldValue::from(5), FieldValue::Integer(5)));
+    assert!(matches!(FieldValue::from(2.5), FieldValue::Float(value) if value == 2.5));
+    assert!(matches!(FieldValue::from("jim"), FieldValue::Text(text) if text == "jim"));
+    assert!(matches!(FieldValue::from(String::from("jim")), FieldValue::Text(text) if text == "jim"));
+    assert!(matches!(FieldValue::from(true), FieldValue::Boolean(true)));
+    assert!(matches!(FieldValue::from(Some(5)), FieldValue::Integer(5)));
+    assert!(matches!(FieldValue::from(None::<i64>), FieldValue::None));
+}
+
+#[test]
+fn values_convert_back_if_theyre_the_right_kind() {
+    assert_eq!(i64::try_from(FieldValue::from(5)).unwrap(), 5);
+    assert_eq!(f64::try_from(FieldValue::from(2.5)).unwrap(), 2.5);
+    assert_eq!(f64::try_from(FieldValue::from(5)).unwrap(), 5.0);
+    assert_eq!(String::try_from(FieldValue::from("jim")).unwrap(), "jim");
+    assert!(bool::try_from(FieldValue::from(true)).unwrap());
+
+    assert!(matches!(i64::try_from(FieldValue::from(2.5)), Err(CoilError::IncompatibleTypes)));
+    assert!(matches!(i64::try_from(FieldValue::from("5")), Err(CoilError::IncompatibleTypes)));
+    assert!(matches!(f64::try_from(FieldValue::from("2.5")), Err(CoilError::IncompatibleTypes)));
+    assert!(matches!(String::try_from(FieldValue::from(5)), Err(CoilError::IncompatibleTypes)));
+    assert!(matches!(bool::try_from(FieldValue::from(1)), Err(CoilError::IncompatibleTypes)));
+    assert!(matches!(i64::try_from(FieldValue::None), Err(CoilError::IncompatibleTypes)));
+}
+
+#[test]
+fn values_display_plainly() {
+    assert_eq!(FieldValue::from("jim \"j\"").to_string(), "jim \"j\"");
+    assert_eq!(FieldValue::from(5).to_string(), "5");
+    assert_eq!(FieldValue::from(2.5).to_string(), "2.5");
+    assert_eq!(FieldValue::from(false).to_string(), "false");
+    assert_eq!(FieldValue::None.to_string(), "None");
+    assert_eq!(FieldValue::from("jim \"j\"").to_literal(), "\"jim \\\"j\\\"\"");
+    assert_eq!(FieldValue::None.to_literal(), "none");
+}
+
+#[test]
+fn errors_display_as_messages() {
+    assert_eq!(CoilError::TableDoesntExist.to_string(), "table does not exist");
+    assert_eq!(CoilError::ColumnDoesntExist(String::from("age")).to_string(), "column `age` does not exist");
+    assert_eq!(CoilError::UniqueViolation(String::from("email")).to_string(), "`email` already has that value, and it's unique");
+    assert_eq!(CoilError::ScanLimitExceeded(10).to_string(), "the query would look at more than 10 rows");
+    assert_eq!(CoilError::DivisionByZero.message(), CoilError::DivisionByZero.to_string());
+}
+
+#[test]
+fn number_columns_take_integers_and_floats() {
+    let mut database = Database::in_memory(String::from("test")).unwrap();
+    execute(&mut database, "create table prices [price: number]").unwrap();
+    execute(&mut database, "put [2.5] in prices").unwrap();
+    execute(&mut database, "put [1] in prices").unwrap();
+    execute(&mut database, "put [1.0] in prices").unwrap();
+    let prices = rows(&mut database, "get price from prices").unwrap();
+    assert_eq!(prices, vec![row![2.5], row![1], row![1]]);
+    // Each value keeps the kind it was written as.
+    assert!(matches!(prices[1][0], FieldValue::Integer(1)));
+    assert!(matches!(prices[2][0], FieldValue::Float(_)));
+    assert_eq!(rows(&mut database, "get sum(price) from prices").unwrap(), vec![row![4.5]]);
+    assert_eq!(rows(&mut database, "get count(*) from prices where price = 1").unwrap(), vec![row![2]]);
+}
+
+fn count_customers(database: &mut Database, condition: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    let query = Query::parse(&format!("get * from customers where {}", condition))?;
+    let result = database.run_query(query)?;
+    Ok(result.rows.map_or(0, |rows| rows.len()))
+}
+
+#[test]
+fn errors_can_be_passed_on_with_a_question_mark() {
+    let mut database = customers();
+    assert_eq!(count_customers(&mut database, "ID > 1").unwrap(), 2);
+    let error = count_customers(&mut database, "age > 1").unwrap_err();
+    assert!(matches!(error.downcast_ref::<CoilError>(), Some(CoilError::ColumnDoesntExist(_))));
+    assert_eq!(error.to_string(), "column `age` does not exist");
+    assert!(count_customers(&mut database, "ID >").unwrap_err().is::<CoilError>());
+}
+
+#[test]
+fn integers_go_up_to_the_64_bit_limit() {
+    let mut database = customers();
+    execute(&mut database, "put [\"max\", 9223372036854775807] in customers").unwrap();
+    execute(&mut database, "put [\"min\", -9223372036854775807] in customers").unwrap();
+    assert_eq!(rows(&mut database, "get Name from customers where ID = 9223372036854775807").unwrap(), vec![row!["max"]]);
+    assert_eq!(rows(&mut database, "get Name from customers where ID > 9223372036854775806").unwrap(), vec![row!["max"]]);
+    assert_eq!(rows(&mut database, "get max(ID), min(ID) from customers").unwrap(),
+               vec![row![i64::MAX, -i64::MAX]]);
+}
+
+#[test]
+fn integers_past_the_limit_are_an_error() {
+    let mut database = customers();
+    match execute(&mut database, "put [\"big\", 9223372036854775808] in customers") {
+        Err(CoilError::InvalidQuery(message)) => { assert!(message.contains("too big"), "{}", message); },
+        other => { panic!("{:?}", other); }
+    }
+    // As a float, it's fine.
+    execute(&mut database, "put [\"big\", 9223372036854775808.0] in customers").unwrap();
+    assert_eq!(rows(&mut database, "get ID from customers where Name = \"big\"").unwrap(),
+               vec![row![9223372036854775808.0]]);
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use coil::*;
+use common::*;
+
+fn count(workspace: &mut Workspace, query: &str) -> Result<usize, CoilError> {
+    let result = workspace.run_query(Query::parse(query)?)?;
+    Ok(result.rows.map_or(0, |rows| rows.len()))
+}
+
+// A workspace with the `test` database (holding
+// `customers`) and an `analytics` one.
+fn workspace() -> Workspace {
+    let mut workspace = Workspace::new();
+    workspace.attach(customers()).unwrap();
+    let mut analytics = Database::in_memory(String::from("analytics")).unwrap();
+    execute(&mut analytics, "create table events [kind: text]").unwrap();
+    execute(&mut analytics, "put [\"click\"] in events").unwrap();
+    workspace.attach(analytics).unwrap();
+    workspace
+}
+
+#[test]
+fn queries_go_to_the_database_their_table_is_qualified_with() {
+    let mut workspace = workspace();
+    assert_eq!(count(&mut workspace, "get * from analytics.events").unwrap(), 1);
+    assert_eq!(count(&mut workspace, "get * from test.customers").unwrap(), 3);
+    count(&mut workspace, "put [\"view\"] in analytics.events").unwrap();
+    assert_eq!(count(&mut workspace, "get * from analytics.events").unwrap(), 2);
+    assert!(matches!(count(&mut workspace, "get * from test.events"), Err(CoilError::TableDoesntExist)));
+    assert!(matches!(count(&mut workspace, "get * from sales.events"), Err(CoilError::DatabaseDoesntExist)));
+}
+
+#[test]
+fn unqualified_tables_are_in_the_current_database() {
+    let mut workspace = workspace();
+    assert_eq!(workspace.current().unwrap().name(), "test");
+    assert_eq!(count(&mut workspace, "get * from customers").unwrap(), 3);
+    workspace.set_current("analytics").unwrap();
+    assert_eq!(count(&mut workspace, "get * from events").unwrap(), 1);
+    assert!(matches!(workspace.set_current("sales"), Err(CoilError::DatabaseDoesntExist)));
+}
+
+#[test]
+fn databases_can_be_created_and_deleted() {
+    let mut workspace = workspace();
+    count(&mut workspace, "create database sales").unwrap();
+    count(&mut workspace, "create table sales.orders [total: number]").unwrap();
+    assert_eq!(count(&mut workspace, "get * from sales.orders").unwrap(), 0);
+    assert!(matches!(count(&mut workspace, "create database sales"), Err(CoilError::DatabaseAlreadyExists)));
+    assert!(matches!(workspace.attach(customers()), Err(CoilError::DatabaseAlreadyExists)));
+
+    count(&mut workspace, "delete database test").unwrap();
+    assert!(workspace.get_database("test").is_none());
+    assert!(workspace.current().is_none());
+    assert!(matches!(count(&mut workspace, "get * from customers"), Err(CoilError::DatabaseDoesntExist)));
+    assert!(matches!(count(&mut workspace, "delete database test"), Err(CoilError::DatabaseDoesntExist)));
+}
+
+#[test]
+fn a_single_database_cant_create_or_delete_databases() {
+    let mut database = customers();
+    assert!(matches!(execute(&mut database, "create database sales"), Err(CoilError::NeedsWorkspace)));
+}